@@ -0,0 +1,32 @@
+//! Timings for the tilting code, run with `cargo bench`. Criterion is not available offline, so this is a small
+//! harness that repeats each operation until it has run for a while and reports the mean time per iteration.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use aoc2023_day14::{Direction, TiltingPlatform};
+
+
+const TARGET: Duration = Duration::from_millis(500);
+
+
+fn bench<T>(name: &str, mut f: impl FnMut() -> T) {
+    let mut iterations: u32 = 0;
+    let start = Instant::now();
+    while start.elapsed() < TARGET {
+        black_box(f());
+        iterations += 1;
+    }
+    println!("{:<32} {:>12?} per iteration ({} iterations)", name, start.elapsed() / iterations, iterations);
+}
+
+fn main() {
+    for size in [10, 50, 100] {
+        let platform = TiltingPlatform::random(size, size, 1.0 / 3.0, 1.0 / 3.0, 42);
+        bench(&format!("tilt north {}x{}", size, size), || platform.tilt(&Direction::North));
+        bench(&format!("tilt west {}x{}", size, size), || platform.tilt(&Direction::West));
+        bench(&format!("spin cycle {}x{}", size, size), || platform.cycles().next());
+        bench(&format!("detect_cycle {}x{}", size, size), || platform.detect_cycle());
+        bench(&format!("cycle 1e9 {}x{}", size, size), || platform.cycle(1_000_000_000));
+    }
+}
@@ -0,0 +1,165 @@
+use std::iter;
+
+use crate::{Direction, TPSpace, SPIN_CYCLE};
+
+
+/// Largest number of rows or columns a platform can have and still be tilted as a `BitGrid`.
+pub(crate) const MAX_SIDE: usize = u128::BITS as usize;
+
+
+/// Bitset form of a platform where every row is stored as two masks, one for the round stones and one for the
+/// square stones, with bit `c` standing for column `c`. Tilting a row only needs a popcount per segment between
+/// square stones, so no intermediate vectors are allocated.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub(crate) struct BitGrid {
+    cols: usize,
+    round: Vec<u128>,
+    square: Vec<u128>
+}
+
+impl BitGrid {
+
+    /// Builds the bitset form of `matrix`, or `None` if it has more than `MAX_SIDE` rows or columns or its rows
+    /// are not all the same length.
+    pub(crate) fn from_matrix(matrix: &[Vec<TPSpace>]) -> Option<Self> {
+        let cols = matrix.first().map_or(0, Vec::len);
+        if matrix.len() > MAX_SIDE || cols > MAX_SIDE || matrix.iter().any(|row| row.len() != cols) {
+            return None;
+        }
+        let mut grid = BitGrid { cols, round: vec![0; matrix.len()], square: vec![0; matrix.len()] };
+        for (r, row) in matrix.iter().enumerate() {
+            for (c, space) in row.iter().enumerate() {
+                match space {
+                    TPSpace::Empty => (),
+                    TPSpace::RoundStone => grid.round[r] |= 1 << c,
+                    TPSpace::SquareStone => grid.square[r] |= 1 << c
+                }
+            }
+        }
+        Some(grid)
+    }
+
+    pub(crate) fn to_matrix(&self) -> Vec<Vec<TPSpace>> {
+        self.round
+            .iter()
+            .zip(self.square.iter())
+            .map(|(round, square)| {
+                let mut row = vec![TPSpace::Empty; self.cols];
+                for c in set_bits(*round) {
+                    row[c] = TPSpace::RoundStone;
+                }
+                for c in set_bits(*square) {
+                    row[c] = TPSpace::SquareStone;
+                }
+                row
+            })
+            .collect()
+    }
+
+    /// Swaps rows and columns, so North/South tilts can be done as West/East tilts on the result.
+    fn transpose(&self) -> Self {
+        let mut out = BitGrid { cols: self.round.len(), round: vec![0; self.cols], square: vec![0; self.cols] };
+        for (r, (round, square)) in self.round.iter().zip(self.square.iter()).enumerate() {
+            for c in set_bits(*round) {
+                out.round[c] |= 1 << r;
+            }
+            for c in set_bits(*square) {
+                out.square[c] |= 1 << r;
+            }
+        }
+        out
+    }
+
+    /// Tilts a single row towards column 0 when `towards_start` is set, or towards the last column otherwise.
+    fn tilt_row(round: u128, square: u128, cols: usize, towards_start: bool) -> u128 {
+        let mut out: u128 = 0;
+        let mut start: usize = 0;
+        let mut walls = square;
+        loop {
+            let end = if walls == 0 { cols } else { walls.trailing_zeros() as usize };
+            let rounds = (round & mask(start, end)).count_ones() as usize;
+            out |= if towards_start { mask(start, start + rounds) } else { mask(end - rounds, end) };
+            if walls == 0 {
+                return out;
+            }
+            walls &= walls - 1;
+            start = end + 1;
+        }
+    }
+
+    fn tilt_rows(&mut self, towards_start: bool) {
+        for (round, square) in self.round.iter_mut().zip(self.square.iter()) {
+            *round = BitGrid::tilt_row(*round, *square, self.cols, towards_start);
+        }
+    }
+
+    /// Tilts the grid towards `direction`. With the `logging` feature the direction and the resulting load are
+    /// logged at debug level and the resulting grid at trace level, the same way `TiltingPlatform::tilt_mut` does.
+    pub(crate) fn tilt(&self, direction: &Direction) -> Self {
+        let out = match direction {
+            Direction::West | Direction::East => {
+                let mut out = self.clone();
+                out.tilt_rows(matches!(direction, Direction::West));
+                out
+            },
+            Direction::North | Direction::South => {
+                let mut out = self.transpose();
+                out.tilt_rows(matches!(direction, Direction::North));
+                out.transpose()
+            }
+        };
+        #[cfg(feature = "logging")]
+        {
+            log::debug!("Tilted {:?}, load is now {}", direction, out.get_load());
+            log::trace!("Platform after tilting {:?}:\n{}", direction, crate::TiltingPlatform::from(out.to_matrix()));
+        }
+        out
+    }
+
+    /// Bitset counterpart of `TiltingPlatform::cycles`.
+    pub(crate) fn cycles(&self) -> impl Iterator<Item = BitGrid> {
+        self.cycles_with(SPIN_CYCLE)
+    }
+
+    /// Same as `cycles`, but with every spin cycle tilting towards the directions of `order`.
+    pub(crate) fn cycles_with(&self, order: [Direction; 4]) -> impl Iterator<Item = BitGrid> {
+        let mut state = self.clone();
+        iter::from_fn(move || {
+            state = order.iter().fold(state.clone(), |out, direction| out.tilt(direction));
+            Some(state.clone())
+        })
+    }
+
+    pub(crate) fn get_load(&self) -> usize {
+        self.round
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(factor, round)| round.count_ones() as usize * (factor + 1))
+            .sum()
+    }
+
+}
+
+
+/// Mask with the bits in `start..end` set.
+fn mask(start: usize, end: usize) -> u128 {
+    match end - start {
+        0 => 0,
+        len if len == MAX_SIDE => !0,
+        len => ((1 << len) - 1) << start
+    }
+}
+
+
+/// Indices of the set bits in `bits`, lowest first.
+fn set_bits(mut bits: u128) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if bits == 0 {
+            return None;
+        }
+        let index = bits.trailing_zeros() as usize;
+        bits &= bits - 1;
+        Some(index)
+    })
+}
@@ -0,0 +1,3668 @@
+mod bits;
+mod packed;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead};
+use std::iter;
+#[cfg(feature = "parallel")]
+use std::num::NonZeroUsize;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use bits::BitGrid;
+pub use packed::PackedPlatform;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TPSpace {
+    Empty,
+    RoundStone,
+    SquareStone
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Direction {
+    North,
+    West,
+    East,
+    South
+}
+
+
+/// Glyphs used to read and write each kind of cell. The default is the puzzle's `.`, `O` and `#`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CharMap {
+    pub empty: char,
+    pub round: char,
+    pub square: char
+}
+
+impl CharMap {
+
+    fn space(&self, c: char) -> Option<TPSpace> {
+        match c {
+            c if c == self.empty => Some(TPSpace::Empty),
+            c if c == self.round => Some(TPSpace::RoundStone),
+            c if c == self.square => Some(TPSpace::SquareStone),
+            _ => None
+        }
+    }
+
+    fn glyph(&self, space: TPSpace) -> char {
+        match space {
+            TPSpace::Empty => self.empty,
+            TPSpace::RoundStone => self.round,
+            TPSpace::SquareStone => self.square
+        }
+    }
+
+}
+
+impl Default for CharMap {
+
+    fn default() -> Self {
+        CharMap { empty: '.', round: 'O', square: '#' }
+    }
+
+}
+
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseError {
+    InvalidChar { row: usize, col: usize, found: char },
+    RaggedGrid { row: usize, expected: usize, found: usize },
+    TooLarge { max: (usize, usize), found: (usize, usize) }
+}
+
+impl fmt::Display for ParseError {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidChar { row, col, found } => write!(
+                f, "Invalid char {:?} at row {}, column {}!", found, row, col
+            ),
+            ParseError::RaggedGrid { row, expected, found } => write!(
+                f, "Row {} has {} columns but {} were expected!", row, found, expected
+            ),
+            ParseError::TooLarge { max, found } => write!(
+                f, "Platform is {}x{} but at most {}x{} is allowed!", found.0, found.1, max.0, max.1
+            )
+        }
+    }
+
+}
+
+impl Error for ParseError {}
+
+
+/// Two platforms that had to be the same size were not.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DimensionError {
+    pub expected: (usize, usize),
+    pub found: (usize, usize)
+}
+
+impl fmt::Display for DimensionError {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f, "Expected a {}x{} platform but found a {}x{} one!",
+            self.expected.0, self.expected.1, self.found.0, self.found.1
+        )
+    }
+
+}
+
+impl Error for DimensionError {}
+
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RegionError {
+    /// Top left corner of the region as `(row, col)`.
+    pub origin: (usize, usize),
+    /// Size of the region as `(rows, cols)`.
+    pub size: (usize, usize),
+    /// Size of the platform as `(rows, cols)`.
+    pub dimensions: (usize, usize)
+}
+
+impl fmt::Display for RegionError {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f, "A {}x{} region at row {}, column {} does not fit in a {}x{} platform!",
+            self.size.0, self.size.1, self.origin.0, self.origin.1, self.dimensions.0, self.dimensions.1
+        )
+    }
+
+}
+
+impl Error for RegionError {}
+
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CycleError {
+    pub max_states: usize
+}
+
+impl fmt::Display for CycleError {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Gave up after storing {} states without finding a loop!", self.max_states)
+    }
+
+}
+
+impl Error for CycleError {}
+
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Parse(ParseError)
+}
+
+impl fmt::Display for LoadError {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "Could not read tilting platform: {}", e),
+            LoadError::Parse(e) => write!(f, "Invalid tilting platform: {}", e)
+        }
+    }
+
+}
+
+impl Error for LoadError {
+
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io(e) => Some(e),
+            LoadError::Parse(e) => Some(e)
+        }
+    }
+
+}
+
+impl From<io::Error> for LoadError {
+
+    fn from(e: io::Error) -> Self {
+        LoadError::Io(e)
+    }
+
+}
+
+impl From<ParseError> for LoadError {
+
+    fn from(e: ParseError) -> Self {
+        LoadError::Parse(e)
+    }
+
+}
+
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CompactError {
+    InvalidHeader,
+    Parse(ParseError),
+    Dimensions(DimensionError)
+}
+
+impl fmt::Display for CompactError {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompactError::InvalidHeader => write!(f, "Compact platform must start with its size as `ROWSxCOLS:`!"),
+            CompactError::Parse(e) => write!(f, "Invalid compact platform: {}", e),
+            CompactError::Dimensions(e) => write!(f, "Compact platform does not match its size: {}", e)
+        }
+    }
+
+}
+
+impl Error for CompactError {
+
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CompactError::InvalidHeader => None,
+            CompactError::Parse(e) => Some(e),
+            CompactError::Dimensions(e) => Some(e)
+        }
+    }
+
+}
+
+impl From<ParseError> for CompactError {
+
+    fn from(e: ParseError) -> Self {
+        CompactError::Parse(e)
+    }
+
+}
+
+impl From<DimensionError> for CompactError {
+
+    fn from(e: DimensionError) -> Self {
+        CompactError::Dimensions(e)
+    }
+
+}
+
+
+/// Two platforms are equal, and hash the same, when their cells are; whether their load has been cached does not
+/// matter. With the `serde` feature the cells are serialized as one array of cells per row, and the cached load is
+/// left out. Deserializing checks that every row is as wide as the first one, like `parse` does.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawPlatform"))]
+pub struct TiltingPlatform {
+    matrix: Vec<Vec<TPSpace>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    load_cache: Option<usize>
+}
+
+impl TiltingPlatform {
+
+    /// Parses a platform from its lines. Trailing whitespace on each line, including the `\r` of CRLF line
+    /// endings, is ignored, as are blank lines at the end of the input and comment lines starting with `//` or
+    /// `;`. Every row must be as wide as the first one, otherwise `ParseError::RaggedGrid` is returned. Rows in
+    /// errors are counted without the comment lines.
+    ///
+    /// # Arguments
+    ///
+    /// - `lines`: one string per row of the platform.
+    pub fn parse(lines: Vec<String>) -> Result<Self, ParseError> {
+        TiltingPlatform::parse_with(lines, &CharMap::default())
+    }
+
+    /// Same as `parse`, but reading the cells with the glyphs of `map` instead of `.`, `O` and `#`. Trailing
+    /// whitespace that is one of the glyphs of `map` is kept as part of the row, and a comment prefix starting
+    /// with one of the glyphs of `map` is read as a row instead.
+    ///
+    /// # Arguments
+    ///
+    /// - `lines`: one string per row of the platform.
+    /// - `map`: glyph used for each kind of cell.
+    pub fn parse_with(lines: Vec<String>, map: &CharMap) -> Result<Self, ParseError> {
+        TiltingPlatform::parse_slice(&lines, map, (usize::MAX, usize::MAX))
+    }
+
+    /// Same as `parse`, but failing with `ParseError::TooLarge` when the platform has more than `max_rows` rows or
+    /// any of its rows is wider than `max_cols`, before anything is allocated for the cells. Useful to guard
+    /// against untrusted input.
+    ///
+    /// # Arguments
+    ///
+    /// - `lines`: one string per row of the platform.
+    /// - `max_rows`: largest number of rows allowed.
+    /// - `max_cols`: largest number of columns allowed.
+    pub fn parse_with_limits(lines: Vec<String>, max_rows: usize, max_cols: usize) -> Result<Self, ParseError> {
+        TiltingPlatform::parse_slice(&lines, &CharMap::default(), (max_rows, max_cols))
+    }
+
+    /// Same as `parse`, but borrowing the lines, so a platform can be written as `&["..#..O..", "..#O...."]`.
+    ///
+    /// # Arguments
+    ///
+    /// - `lines`: one string per row of the platform.
+    pub fn from_lines(lines: &[&str]) -> Result<Self, ParseError> {
+        TiltingPlatform::parse_slice(lines, &CharMap::default(), (usize::MAX, usize::MAX))
+    }
+
+    fn parse_slice<S: AsRef<str>>(lines: &[S], map: &CharMap, max: (usize, usize)) -> Result<Self, ParseError> {
+        let lines = TiltingPlatform::trim_lines(lines, map);
+        let expected = lines.first().map_or(0, |l| l.chars().count());
+        let widest = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        if lines.len() > max.0 || widest > max.1 {
+            return Err(ParseError::TooLarge { max, found: (lines.len(), widest) });
+        }
+        Ok(TiltingPlatform { 
+            matrix: lines
+                .into_iter()
+                .enumerate()
+                .map(|(row, l)| {
+                    let found = l.chars().count();
+                    if found != expected {
+                        return Err(ParseError::RaggedGrid { row, expected, found });
+                    }
+                    l
+                        .chars()
+                        .enumerate()
+                        .map(|(col, c)| map.space(c).ok_or(ParseError::InvalidChar { row, col, found: c }))
+                        .collect()
+                })
+                .collect::<Result<_, _>>()?,
+            load_cache: None
+        })
+    }
+
+    /// Builds a `rows` x `cols` platform where each cell is independently a round stone with probability
+    /// `round_density`, a square stone with probability `square_density` and empty otherwise. The cells are drawn
+    /// from a xorshift generator seeded with `seed`, so the same arguments always give the same platform.
+    ///
+    /// # Panics
+    ///
+    /// If either density is negative or they add up to more than 1.
+    pub fn random(rows: usize, cols: usize, round_density: f64, square_density: f64, seed: u64) -> Self {
+        assert!(
+            round_density >= 0.0 && square_density >= 0.0 && round_density + square_density <= 1.0,
+            "Densities must be non negative and add up to at most 1!"
+        );
+        let mut rng = XorShift::new(seed);
+        TiltingPlatform {
+            matrix: (0..rows)
+                .map(|_| (0..cols)
+                    .map(|_| match rng.next_f64() {
+                        x if x < round_density => TPSpace::RoundStone,
+                        x if x < round_density + square_density => TPSpace::SquareStone,
+                        _ => TPSpace::Empty
+                    })
+                    .collect()
+                )
+                .collect(),
+            load_cache: None
+        }
+    }
+
+    /// Forgiving version of `parse` that reads any character it does not recognise as an empty cell and pads rows
+    /// shorter than the widest one with empty cells, so it never fails. The skipped characters are returned as
+    /// `(row, col, char)` alongside the platform so the caller can warn about them.
+    ///
+    /// # Arguments
+    ///
+    /// - `lines`: one string per row of the platform.
+    pub fn parse_lenient(lines: Vec<String>) -> (Self, Vec<(usize, usize, char)>) {
+        let map = CharMap::default();
+        let lines = TiltingPlatform::trim_lines(&lines, &map);
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut skipped: Vec<(usize, usize, char)> = vec![];
+        let matrix = lines
+            .into_iter()
+            .enumerate()
+            .map(|(row, l)| {
+                let mut spaces: Vec<TPSpace> = l
+                    .chars()
+                    .enumerate()
+                    .map(|(col, c)| map.space(c).unwrap_or_else(|| {
+                        skipped.push((row, col, c));
+                        TPSpace::Empty
+                    }))
+                    .collect();
+                spaces.resize(width, TPSpace::Empty);
+                spaces
+            })
+            .collect();
+        (TiltingPlatform { matrix, load_cache: None }, skipped)
+    }
+
+    /// Parses several platforms stored one after another, separated by blank lines. Each platform is parsed on its
+    /// own like `parse` does, so comment lines are skipped and rows in errors are counted from the start of the
+    /// platform they belong to. Runs of blank lines, and platforms made only of comments, yield no platform.
+    ///
+    /// # Arguments
+    ///
+    /// - `lines`: the rows of every platform, with blank lines in between.
+    pub fn parse_multi(lines: Vec<String>) -> Result<Vec<Self>, ParseError> {
+        let map = CharMap::default();
+        let mut platforms: Vec<TiltingPlatform> = vec![];
+        let mut group: Vec<String> = vec![];
+        for line in lines.into_iter().chain(iter::once(String::new())) {
+            if TiltingPlatform::trim_line(&line, &map).is_empty() {
+                if !TiltingPlatform::trim_lines(&group, &map).is_empty() {
+                    platforms.push(TiltingPlatform::parse(group)?);
+                }
+                group = vec![];
+            } else {
+                group.push(line);
+            }
+        }
+        Ok(platforms)
+    }
+
+    /// Strips the trailing whitespace that is not a glyph of `map` from every line, and drops the comment lines
+    /// and the blank lines at the end.
+    fn trim_lines<'a, S: AsRef<str>>(lines: &'a [S], map: &CharMap) -> Vec<&'a str> {
+        let mut lines: Vec<&str> = lines
+            .iter()
+            .map(|l| l.as_ref())
+            .filter(|l| !TiltingPlatform::is_comment(l, map))
+            .map(|l| TiltingPlatform::trim_line(l, map))
+            .collect();
+        while lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+        lines
+    }
+
+    fn trim_line<'a>(line: &'a str, map: &CharMap) -> &'a str {
+        line.trim_end_matches(|c: char| c.is_whitespace() && map.space(c).is_none())
+    }
+
+    /// Whether `line` is a comment, that is whether it starts with `//` or `;` and that prefix cannot be read as
+    /// cells with the glyphs of `map`.
+    fn is_comment(line: &str, map: &CharMap) -> bool {
+        ["//", ";"]
+            .iter()
+            .any(|prefix| line.starts_with(prefix) && prefix.chars().all(|c| map.space(c).is_none()))
+    }
+
+    /// Reads and parses the platform stored at `path`, or from standard input when `path` is `-`. With the `gzip`
+    /// feature the input is decompressed first when `path` ends in `.gz` or the data starts with the gzip magic
+    /// bytes.
+    pub fn load(path: &str) -> Result<Self, LoadError> {
+        if path == "-" {
+            TiltingPlatform::from_reader_decompressing(io::stdin().lock(), false)
+        } else {
+            let reader = io::BufReader::new(fs::File::open(path)?);
+            TiltingPlatform::from_reader_decompressing(reader, path.ends_with(".gz"))
+        }
+    }
+
+    /// Same as `from_reader`, but with the `gzip` feature `reader` is decompressed before parsing when `gzipped` is
+    /// set or its data starts with the gzip magic bytes.
+    #[cfg(feature = "gzip")]
+    fn from_reader_decompressing<R: BufRead>(mut reader: R, gzipped: bool) -> Result<Self, LoadError> {
+        if gzipped || reader.fill_buf()?.starts_with(&[0x1f, 0x8b]) {
+            TiltingPlatform::from_reader(io::BufReader::new(flate2::bufread::GzDecoder::new(reader)))
+        } else {
+            TiltingPlatform::from_reader(reader)
+        }
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    fn from_reader_decompressing<R: BufRead>(reader: R, _gzipped: bool) -> Result<Self, LoadError> {
+        TiltingPlatform::from_reader(reader)
+    }
+
+    /// Reads every line of `reader` and parses them as a platform.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, LoadError> {
+        Ok(TiltingPlatform::parse(reader.lines().collect::<Result<_, _>>()?)?)
+    }
+
+    pub fn get_load(&self) -> usize {
+        self.get_load_weighted(1)
+    }
+
+    /// Same as `get_load`, but with every round stone weighing `weight` instead of one, so each stone contributes
+    /// `weight` times its distance from the bottom edge.
+    ///
+    /// # Arguments
+    ///
+    /// - `weight`: weight of a single round stone.
+    pub fn get_load_weighted(&self, weight: usize) -> usize {
+        self.matrix.iter().rev().enumerate().map(|(factor, row)| {
+            row.iter().filter(|s| **s == TPSpace::RoundStone).count() * (factor + 1) * weight
+        }).sum()
+    }
+
+    /// Load measured against `edge` instead of the North one: every round stone weighs its distance from the
+    /// opposite edge, counting its own row or column, so `get_load_from(&Direction::North)` is `get_load`.
+    ///
+    /// # Arguments
+    ///
+    /// - `edge`: the edge the load is measured against.
+    pub fn get_load_from(&self, edge: &Direction) -> usize {
+        let (rows, cols) = self.dimensions();
+        self.round_positions()
+            .map(|(row, col)| match edge {
+                Direction::North => rows - row,
+                Direction::South => row + 1,
+                Direction::West => cols - col,
+                Direction::East => col + 1
+            })
+            .sum()
+    }
+
+    /// Same as `get_load`, but the load is only computed the first time and kept until the platform is tilted or
+    /// one of its cells is `set`, so asking again for the load of an unchanged state is free.
+    pub fn get_load_cached(&mut self) -> usize {
+        let load = self.load_cache.unwrap_or_else(|| self.get_load());
+        self.load_cache = Some(load);
+        load
+    }
+
+    /// Load contributed by the round stones of each column, weighting every stone by its distance from the bottom
+    /// edge like `get_load` does. The loads add up to `get_load`.
+    pub fn column_loads(&self) -> Vec<usize> {
+        let (rows, cols) = self.dimensions();
+        let mut loads = vec![0; cols];
+        self.round_positions().for_each(|(row, col)| loads[col] += rows - row);
+        loads
+    }
+
+    /// Average `(row, col)` position of the round stones, each one weighing the same. Returns `None` when there
+    /// are no round stones, as the average of no positions is undefined.
+    pub fn center_of_mass(&self) -> Option<(f64, f64)> {
+        let (count, rows, cols) = self.round_positions()
+            .fold((0, 0, 0), |(count, rows, cols), (row, col)| (count + 1, rows + row, cols + col));
+        match count {
+            0 => None,
+            _ => Some((rows as f64 / count as f64, cols as f64 / count as f64))
+        }
+    }
+
+    /// Size of the platform as `(rows, cols)`, `(0, 0)` for an empty platform.
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.matrix.len(), self.matrix.first().map_or(0, Vec::len))
+    }
+
+    /// Borrows the rows of the platform.
+    pub fn rows(&self) -> &[Vec<TPSpace>] {
+        &self.matrix
+    }
+
+    /// Every cell of the platform as `(row, col, space)`, in row-major order.
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize, TPSpace)> + '_ {
+        self.matrix
+            .iter()
+            .enumerate()
+            .flat_map(|(row, spaces)| spaces.iter().enumerate().map(move |(col, space)| (row, col, *space)))
+    }
+
+    /// Coordinates of the round stones, in row-major order.
+    pub fn round_positions(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.cells().filter(|(_, _, space)| *space == TPSpace::RoundStone).map(|(row, col, _)| (row, col))
+    }
+
+    /// Contents of the cell at `row`, `col`, or `None` if the coordinate is out of bounds. Indexing with
+    /// `platform[(row, col)]` does the same but panics instead.
+    pub fn get(&self, row: usize, col: usize) -> Option<TPSpace> {
+        self.matrix.get(row).and_then(|r| r.get(col)).copied()
+    }
+
+    /// Replaces the contents of the cell at `row`, `col`, returning whether the coordinate was in bounds. Assigning
+    /// to `platform[(row, col)]` does the same but panics instead.
+    pub fn set(&mut self, row: usize, col: usize, space: TPSpace) -> bool {
+        match self.matrix.get_mut(row).and_then(|r| r.get_mut(col)) {
+            Some(cell) => {
+                *cell = space;
+                self.load_cache = None;
+                true
+            },
+            None => false
+        }
+    }
+
+    /// Number of round stones resting against each of the four edges, that is in the first or last row or column.
+    pub fn edge_counts(&self) -> EdgeCounts {
+        let (rows, cols) = self.dimensions();
+        let mut counts = EdgeCounts::default();
+        for (row, col) in self.round_positions() {
+            counts.north += (row == 0) as usize;
+            counts.south += (row == rows - 1) as usize;
+            counts.west += (col == 0) as usize;
+            counts.east += (col == cols - 1) as usize;
+        }
+        counts
+    }
+
+    /// Number of round stones on the platform, which no tilt can change.
+    pub fn count_round(&self) -> usize {
+        self.round_positions().count()
+    }
+
+    /// Number of square stones on the platform.
+    pub fn count_square(&self) -> usize {
+        self.count(TPSpace::SquareStone)
+    }
+
+    fn count(&self, space: TPSpace) -> usize {
+        self.cells().filter(|(_, _, s)| *s == space).count()
+    }
+
+    /// Rotates the platform clockwise by `quarter_turns` * 90 degrees, returning the result as a new platform.
+    pub fn rotate(&self, quarter_turns: usize) -> Self {
+        TiltingPlatform { matrix: TiltingPlatform::rotate_matrix(&self.matrix, quarter_turns), load_cache: None }
+    }
+
+    /// Whether `other` is this platform rotated by some number of quarter turns, including none.
+    pub fn eq_up_to_rotation(&self, other: &Self) -> bool {
+        (0..4).any(|quarter_turns| TiltingPlatform::rotate_matrix(&self.matrix, quarter_turns) == other.matrix)
+    }
+
+    /// Swaps the rows and columns of the platform, so an R x C platform becomes a C x R one.
+    pub fn transpose(&self) -> Self {
+        TiltingPlatform {
+            matrix: (0..self.dimensions().1)
+                .map(|col| self.matrix.iter().map(|row| row[col]).collect())
+                .collect(),
+            load_cache: None
+        }
+    }
+
+    /// Surrounds the platform with `top`, `bottom`, `left` and `right` rows or columns of empty cells, returning
+    /// the result as a new platform.
+    pub fn pad(&self, top: usize, bottom: usize, left: usize, right: usize) -> Self {
+        let cols = left + self.dimensions().1 + right;
+        let empty_rows = |count: usize| iter::repeat_n(vec![TPSpace::Empty; cols], count);
+        TiltingPlatform {
+            matrix: empty_rows(top)
+                .chain(self.matrix.iter().map(|row| {
+                    let mut padded = vec![TPSpace::Empty; left];
+                    padded.extend_from_slice(row);
+                    padded.resize(cols, TPSpace::Empty);
+                    padded
+                }))
+                .chain(empty_rows(bottom))
+                .collect(),
+            load_cache: None
+        }
+    }
+
+    /// Copies the `height` x `width` region whose top left cell is at `row`, `col` into a new platform. Returns a
+    /// `RegionError` if any part of the region falls outside the platform.
+    pub fn subgrid(&self, row: usize, col: usize, height: usize, width: usize) -> Result<Self, RegionError> {
+        let (rows, cols) = self.dimensions();
+        let fits = |start: usize, len: usize, side: usize| start.checked_add(len).is_some_and(|end| end <= side);
+        if !fits(row, height, rows) || !fits(col, width, cols) {
+            return Err(RegionError { origin: (row, col), size: (height, width), dimensions: (rows, cols) });
+        }
+        Ok(TiltingPlatform::from(
+            self.matrix[row..row + height].iter().map(|r| r[col..col + width].to_vec()).collect::<Vec<_>>()
+        ))
+    }
+
+    fn rotate_matrix(matrix: &[Vec<TPSpace>], times: usize) -> Vec<Vec<TPSpace>> {
+        match times % 4 {
+            0 => matrix.to_vec(),
+            1 => (0..matrix.first().map_or(0, Vec::len)).map(| i |
+                matrix.iter().map(|row| row[i]).rev().collect()
+            ).collect(),
+            2 => matrix.iter().map(|row| row.iter().rev().copied().collect()).rev().collect(),
+            3 => (0..matrix.first().map_or(0, Vec::len)).rev().map(| i |
+                matrix.iter().map(|row| row[i]).collect()
+            ).collect(),
+            _ => panic!("This is impossible!")
+        }
+        
+    }
+
+    /// Slides the round stones of `row` towards its start in place, without allocating a new row.
+    fn tilt_row(row: &mut [TPSpace]) {
+        let mut free: usize = 0;
+        for i in 0..row.len() {
+            match row[i] {
+                TPSpace::Empty => (),
+                TPSpace::RoundStone => {
+                    row[i] = TPSpace::Empty;
+                    row[free] = TPSpace::RoundStone;
+                    free += 1;
+                },
+                TPSpace::SquareStone => free = i + 1
+            }
+        }
+    }
+
+    /// Tilts every row towards its start when `towards_start` is set, or towards its end otherwise.
+    #[cfg(not(feature = "parallel"))]
+    fn tilt_rows(rows: &mut [Vec<TPSpace>], towards_start: bool) {
+        rows.iter_mut().for_each(|row| TiltingPlatform::tilt_row_towards(row, towards_start));
+    }
+
+    /// Tilts every row towards its start when `towards_start` is set, or towards its end otherwise. Grids of at
+    /// least `PARALLEL_MIN_CELLS` cells are split into one contiguous chunk per available core and each chunk is
+    /// tilted on its own thread; smaller grids are tilted in place since spawning would cost more than the tilt.
+    #[cfg(feature = "parallel")]
+    fn tilt_rows(rows: &mut [Vec<TPSpace>], towards_start: bool) {
+        let cells = rows.len() * rows.first().map_or(0, Vec::len);
+        let threads = if cells < PARALLEL_MIN_CELLS {
+            1
+        } else {
+            thread::available_parallelism().map_or(1, NonZeroUsize::get)
+        };
+        if threads == 1 {
+            rows.iter_mut().for_each(|row| TiltingPlatform::tilt_row_towards(row, towards_start));
+            return;
+        }
+        let chunk_len = rows.len().div_ceil(threads).max(1);
+        thread::scope(|scope| {
+            for chunk in rows.chunks_mut(chunk_len) {
+                scope.spawn(move || {
+                    chunk.iter_mut().for_each(|row| TiltingPlatform::tilt_row_towards(row, towards_start));
+                });
+            }
+        });
+    }
+
+    fn tilt_row_towards(row: &mut [TPSpace], towards_start: bool) {
+        if towards_start {
+            TiltingPlatform::tilt_row(row);
+        } else {
+            row.reverse();
+            TiltingPlatform::tilt_row(row);
+            row.reverse();
+        }
+    }
+
+    /// Slides the round stones of column `col` towards row 0 when `towards_start` is set, or towards the last row
+    /// otherwise, in place.
+    fn tilt_column(matrix: &mut [Vec<TPSpace>], col: usize, towards_start: bool) {
+        let rows = matrix.len();
+        let at = |k: usize| if towards_start { k } else { rows - 1 - k };
+        let mut free: usize = 0;
+        for k in 0..rows {
+            match matrix[at(k)][col] {
+                TPSpace::Empty => (),
+                TPSpace::RoundStone => {
+                    matrix[at(k)][col] = TPSpace::Empty;
+                    matrix[at(free)][col] = TPSpace::RoundStone;
+                    free += 1;
+                },
+                TPSpace::SquareStone => free = k + 1
+            }
+        }
+    }
+
+    /// Splits every line the stones slide on when tilting towards `direction`, the rows for West and East and the
+    /// columns for North and South, into the runs of cells between square stones. A tilt only moves stones within
+    /// their segment, so this tells how full each one is without tilting. Segments are listed lane by lane from the
+    /// North or West edge and empty runs between adjacent square stones are left out; opposite directions give the
+    /// same segments.
+    ///
+    /// # Arguments
+    ///
+    /// - `direction`: the edge the stones would slide towards.
+    pub fn segments(&self, direction: &Direction) -> Vec<Segment> {
+        let (rows, cols) = self.dimensions();
+        let (lanes, len) = match direction {
+            Direction::West | Direction::East => (rows, cols),
+            Direction::North | Direction::South => (cols, rows)
+        };
+        let mut segments: Vec<Segment> = vec![];
+        for lane in 0..lanes {
+            let cell = |i: usize| match direction {
+                Direction::West | Direction::East => self.matrix[lane][i],
+                Direction::North | Direction::South => self.matrix[i][lane]
+            };
+            let mut start = 0;
+            let mut round = 0;
+            for i in 0..=len {
+                match (i < len).then(|| cell(i)) {
+                    Some(TPSpace::Empty) => (),
+                    Some(TPSpace::RoundStone) => round += 1,
+                    Some(TPSpace::SquareStone) | None => {
+                        if i > start {
+                            segments.push(Segment { lane, start, len: i - start, round });
+                        }
+                        start = i + 1;
+                        round = 0;
+                    }
+                }
+            }
+        }
+        segments
+    }
+
+    /// Number of round stones that end up piled against each square stone or edge after tilting towards
+    /// `direction`, one entry per segment of `segments` in the same order. Each segment is closed on the
+    /// `direction` side by the obstacle its stones settle against, so this is where the stones accumulate.
+    ///
+    /// # Arguments
+    ///
+    /// - `direction`: the edge the stones slide towards.
+    pub fn settle_histogram(&self, direction: &Direction) -> Vec<usize> {
+        self.segments(direction).iter().map(|segment| segment.round).collect()
+    }
+
+    /// Largest number of cells a single round stone travels when tilting towards `direction`, 0 if the platform is
+    /// already settled. Each row or column is scanned from the edge the stones slide towards, keeping track of the
+    /// first free cell like `tilt_row` does, without moving anything.
+    ///
+    /// # Arguments
+    ///
+    /// - `direction`: the edge the stones slide towards.
+    pub fn max_slide(&self, direction: &Direction) -> usize {
+        let (rows, cols) = self.dimensions();
+        let (lanes, len) = match direction {
+            Direction::West | Direction::East => (rows, cols),
+            Direction::North | Direction::South => (cols, rows)
+        };
+        let mut max = 0;
+        for lane in 0..lanes {
+            let cell = |k: usize| match direction {
+                Direction::West => self.matrix[lane][k],
+                Direction::East => self.matrix[lane][cols - 1 - k],
+                Direction::North => self.matrix[k][lane],
+                Direction::South => self.matrix[rows - 1 - k][lane]
+            };
+            let mut free = 0;
+            for k in 0..len {
+                match cell(k) {
+                    TPSpace::Empty => (),
+                    TPSpace::RoundStone => {
+                        max = max.max(k - free);
+                        free += 1;
+                    },
+                    TPSpace::SquareStone => free = k + 1
+                }
+            }
+        }
+        max
+    }
+
+    /// Tilts the platform so every round stone slides towards `direction` until it hits a square stone, another
+    /// round stone or the edge, returning the result as a new platform.
+    ///
+    /// # Arguments
+    ///
+    /// - `direction`: the edge the stones slide towards.
+    pub fn tilt(&self, direction: &Direction) -> Self {
+        let mut out = self.clone();
+        out.tilt_mut(direction);
+        out
+    }
+
+    /// Same as `tilt`, but also returns the load of the tilted platform, measured against the North edge like
+    /// `get_load`. Each row or column is scanned from the edge the stones slide towards and every stone adds its
+    /// load where it lands, so no separate pass over the result is needed. The load is also cached on the result
+    /// for `get_load_cached`.
+    ///
+    /// # Arguments
+    ///
+    /// - `direction`: the edge the stones slide towards.
+    pub fn tilt_with_load(&self, direction: &Direction) -> (Self, usize) {
+        let mut out = self.clone();
+        let (rows, cols) = self.dimensions();
+        let (lanes, len) = match direction {
+            Direction::West | Direction::East => (rows, cols),
+            Direction::North | Direction::South => (cols, rows)
+        };
+        let mut load = 0;
+        for lane in 0..lanes {
+            let at = |k: usize| match direction {
+                Direction::West => (lane, k),
+                Direction::East => (lane, cols - 1 - k),
+                Direction::North => (k, lane),
+                Direction::South => (rows - 1 - k, lane)
+            };
+            let mut free = 0;
+            for k in 0..len {
+                let (row, col) = at(k);
+                match out.matrix[row][col] {
+                    TPSpace::Empty => (),
+                    TPSpace::RoundStone => {
+                        let (to_row, to_col) = at(free);
+                        out.matrix[row][col] = TPSpace::Empty;
+                        out.matrix[to_row][to_col] = TPSpace::RoundStone;
+                        load += rows - to_row;
+                        free += 1;
+                    },
+                    TPSpace::SquareStone => free = k + 1
+                }
+            }
+        }
+        out.load_cache = Some(load);
+        (out, load)
+    }
+
+    /// Same as `tilt`, but also returns whether any stone moved, that is whether the tilted platform differs from
+    /// this one.
+    ///
+    /// # Arguments
+    ///
+    /// - `direction`: the edge the stones slide towards.
+    pub fn tilt_checked(&self, direction: &Direction) -> (Self, bool) {
+        let out = self.tilt(direction);
+        let moved = out.matrix.iter().zip(self.matrix.iter()).any(|(row, before)| row != before);
+        (out, moved)
+    }
+
+    /// In place version of `tilt`. West and East tilts slide the stones along each row and North and South tilts
+    /// along each column, so the matrix is never rotated or reallocated. With the `parallel` feature the rows of
+    /// West and East tilts are spread across threads. With the `logging` feature the direction and the resulting
+    /// load are logged at debug level and the resulting platform at trace level.
+    ///
+    /// # Arguments
+    ///
+    /// - `direction`: the edge the stones slide towards.
+    pub fn tilt_mut(&mut self, direction: &Direction) {
+        self.load_cache = None;
+        match direction {
+            Direction::West | Direction::East => {
+                TiltingPlatform::tilt_rows(&mut self.matrix, matches!(direction, Direction::West));
+            },
+            Direction::North | Direction::South => {
+                let towards_start = matches!(direction, Direction::North);
+                for col in 0..self.dimensions().1 {
+                    TiltingPlatform::tilt_column(&mut self.matrix, col, towards_start);
+                }
+            }
+        }
+        #[cfg(feature = "logging")]
+        {
+            log::debug!("Tilted {:?}, load is now {}", direction, self.get_load());
+            log::trace!("Platform after tilting {:?}:\n{}", direction, self);
+        }
+    }
+
+    /// Slides the round stones of `row` towards its start as if the row was a ring, so stones that go past the
+    /// start come back in at the end until they meet an obstacle. A row without square stones has nothing for the
+    /// stones to settle against and is left untouched.
+    fn tilt_row_wrapping(row: &mut [TPSpace]) {
+        if let Some(wall) = row.iter().position(|s| *s == TPSpace::SquareStone) {
+            row.rotate_left(wall + 1);
+            TiltingPlatform::tilt_row(row);
+            row.rotate_right(wall + 1);
+        }
+    }
+
+    /// Toroidal variant of `tilt`, where the platform wraps around at its edges: a stone that slides off one edge
+    /// comes back in from the opposite one and keeps going until it hits a square stone or another round stone.
+    /// Rows or columns without any square stone are left as they are.
+    ///
+    /// # Arguments
+    ///
+    /// - `direction`: the edge the stones slide towards.
+    pub fn tilt_wrapping(&self, direction: &Direction) -> Self {
+        let mut out = self.clone();
+        out.load_cache = None;
+        match direction {
+            Direction::West | Direction::East => {
+                for row in out.matrix.iter_mut() {
+                    if matches!(direction, Direction::East) {
+                        row.reverse();
+                    }
+                    TiltingPlatform::tilt_row_wrapping(row);
+                    if matches!(direction, Direction::East) {
+                        row.reverse();
+                    }
+                }
+            },
+            Direction::North | Direction::South => {
+                for col in 0..out.dimensions().1 {
+                    let mut line: Vec<TPSpace> = out.matrix.iter().map(|row| row[col]).collect();
+                    if matches!(direction, Direction::South) {
+                        line.reverse();
+                    }
+                    TiltingPlatform::tilt_row_wrapping(&mut line);
+                    if matches!(direction, Direction::South) {
+                        line.reverse();
+                    }
+                    for (row, space) in out.matrix.iter_mut().zip(line) {
+                        row[col] = space;
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Same as `tilt`, but also reports where every round stone went. The result has one `Move` per round stone,
+    /// including the ones that stay put, with both ends given as `(row, col)` in the platform's own orientation.
+    ///
+    /// # Arguments
+    ///
+    /// - `direction`: the edge the stones slide towards.
+    pub fn tilt_with_moves(&self, direction: &Direction) -> (Self, Vec<Move>) {
+        let (rows, cols) = self.dimensions();
+        let (lines, len) = match direction {
+            Direction::West | Direction::East => (rows, cols),
+            Direction::North | Direction::South => (cols, rows)
+        };
+        // Position `k` along `line`, counting from the edge the stones slide towards.
+        let at = |line: usize, k: usize| match direction {
+            Direction::West => (line, k),
+            Direction::East => (line, cols - 1 - k),
+            Direction::North => (k, line),
+            Direction::South => (rows - 1 - k, line)
+        };
+        let mut moves: Vec<Move> = vec![];
+        for line in 0..lines {
+            let mut free: usize = 0;
+            for k in 0..len {
+                let (row, col) = at(line, k);
+                match self.matrix[row][col] {
+                    TPSpace::Empty => (),
+                    TPSpace::RoundStone => {
+                        moves.push(Move { from: (row, col), to: at(line, free) });
+                        free += 1;
+                    },
+                    TPSpace::SquareStone => free = k + 1
+                }
+            }
+        }
+        let mut out = self.clone();
+        out.load_cache = None;
+        for m in moves.iter() {
+            out.matrix[m.from.0][m.from.1] = TPSpace::Empty;
+        }
+        for m in moves.iter() {
+            out.matrix[m.to.0][m.to.1] = TPSpace::RoundStone;
+        }
+        (out, moves)
+    }
+
+    /// Advances every round stone by at most one cell towards `direction`, moving it only if the neighbouring cell
+    /// was empty before the step. Returns the new platform and whether any stone moved, so calling it until it
+    /// returns `false` settles the platform exactly like `tilt` does, one cell at a time.
+    ///
+    /// # Arguments
+    ///
+    /// - `direction`: the edge the stones slide towards.
+    pub fn tilt_step(&self, direction: &Direction) -> (Self, bool) {
+        let mut out = self.clone();
+        out.load_cache = None;
+        let mut moved = false;
+        for (row, spaces) in self.matrix.iter().enumerate() {
+            for (col, space) in spaces.iter().enumerate() {
+                if *space != TPSpace::RoundStone {
+                    continue;
+                }
+                let next = TiltingPlatform::neighbour(row, col, direction);
+                if let Some((r, c)) = next.filter(|(r, c)| self.get(*r, *c) == Some(TPSpace::Empty)) {
+                    out.matrix[row][col] = TPSpace::Empty;
+                    out.matrix[r][c] = TPSpace::RoundStone;
+                    moved = true;
+                }
+            }
+        }
+        (out, moved)
+    }
+
+    /// Whether tilting the platform towards `direction` would leave it unchanged, that is whether no round stone
+    /// has an empty cell right next to it on that side. Stops at the first stone that could still move, without
+    /// building the tilted platform.
+    ///
+    /// # Arguments
+    ///
+    /// - `direction`: the edge the stones would slide towards.
+    pub fn is_settled(&self, direction: &Direction) -> bool {
+        self.matrix.iter().enumerate().all(|(row, spaces)| {
+            spaces.iter().enumerate().all(|(col, space)| {
+                *space != TPSpace::RoundStone
+                    || TiltingPlatform::neighbour(row, col, direction)
+                        .is_none_or(|(r, c)| self.get(r, c) != Some(TPSpace::Empty))
+            })
+        })
+    }
+
+    /// Drops a round stone into the platform from the edge opposite `direction` and lets it slide towards
+    /// `direction` until it meets a stone or the edge, leaving the rest of the platform untouched. The stone goes
+    /// down column `lane` for North and South, and along row `lane` for West and East. Returns where the stone came
+    /// to rest, or `None` if the cell it enters through is already taken or `lane` is out of bounds.
+    ///
+    /// # Arguments
+    ///
+    /// - `lane`: column, or row for West and East, the stone is dropped into.
+    /// - `direction`: the edge the stone slides towards.
+    pub fn drop_stone(&mut self, lane: usize, direction: &Direction) -> Option<(usize, usize)> {
+        let (rows, cols) = self.dimensions();
+        let entry = match direction {
+            Direction::North => (rows.checked_sub(1)?, lane),
+            Direction::South => (0, lane),
+            Direction::West => (lane, cols.checked_sub(1)?),
+            Direction::East => (lane, 0)
+        };
+        if self.get(entry.0, entry.1) != Some(TPSpace::Empty) {
+            return None;
+        }
+        let mut at = entry;
+        while let Some(next) = TiltingPlatform::neighbour(at.0, at.1, direction)
+            .filter(|(r, c)| self.get(*r, *c) == Some(TPSpace::Empty))
+        {
+            at = next;
+        }
+        self.set(at.0, at.1, TPSpace::RoundStone);
+        Some(at)
+    }
+
+    /// Coordinate of the cell next to `row`, `col` on the `direction` side, or `None` past the North or West edge.
+    /// Past the South and East edges the coordinate is out of bounds instead.
+    fn neighbour(row: usize, col: usize, direction: &Direction) -> Option<(usize, usize)> {
+        match direction {
+            Direction::North => row.checked_sub(1).map(|r| (r, col)),
+            Direction::South => Some((row + 1, col)),
+            Direction::West => col.checked_sub(1).map(|c| (row, c)),
+            Direction::East => Some((row, col + 1))
+        }
+    }
+
+    /// Slowest and most obviously correct way of tilting: moves every round stone one cell at a time with
+    /// `tilt_step` until no stone can move. It is kept as an oracle to check `tilt` against, not for real use.
+    ///
+    /// # Arguments
+    ///
+    /// - `direction`: the edge the stones slide towards.
+    pub fn tilt_reference(&self, direction: &Direction) -> Self {
+        let mut state = self.clone();
+        loop {
+            let (next, moved) = state.tilt_step(direction);
+            if !moved {
+                return state;
+            }
+            state = next;
+        }
+    }
+
+    /// Tilts the platform towards `direction` over and over until doing so no longer changes it, returning the
+    /// stable platform and the number of tilts that changed something. With the current tilt rule a single tilt
+    /// already settles every stone, so the count is 1 for an unsettled platform and 0 for a settled one.
+    ///
+    /// # Arguments
+    ///
+    /// - `direction`: the edge the stones slide towards.
+    pub fn tilt_to_equilibrium(&self, direction: &Direction) -> (Self, usize) {
+        let mut state = self.clone();
+        let mut tilts: usize = 0;
+        loop {
+            let (next, moved) = state.tilt_checked(direction);
+            if !moved {
+                return (state, tilts);
+            }
+            state = next;
+            tilts += 1;
+        }
+    }
+
+    /// Tilts the platform towards each of `directions` in order, returning the result as a new platform.
+    ///
+    /// # Arguments
+    ///
+    /// - `directions`: the edges the stones slide towards, one tilt each.
+    pub fn tilt_sequence(&self, directions: &[Direction]) -> Self {
+        let mut out = self.clone();
+        out.tilt_sequence_mut(directions);
+        out
+    }
+
+    /// In place version of `tilt_sequence`.
+    pub fn tilt_sequence_mut(&mut self, directions: &[Direction]) {
+        directions.iter().for_each(|direction| self.tilt_mut(direction));
+    }
+
+    /// Runs exactly one spin cycle, tilting the platform North, West, South and East in that order, returning the
+    /// result as a new platform. Same as `tilt_sequence` with those four directions.
+    pub fn spin(&self) -> Self {
+        let mut out = self.clone();
+        out.spin_mut();
+        out
+    }
+
+    /// In place version of `spin`.
+    pub fn spin_mut(&mut self) {
+        self.tilt_sequence_mut(&SPIN_CYCLE);
+    }
+
+    pub fn cycle_brute_force(&self, times: usize) -> Self {
+        self.cycle_brute_force_with_progress(times, 0, |_| ())
+    }
+
+    /// Same as `cycle_brute_force`, but calls `f` with the number of cycles completed so far every `report_every`
+    /// cycles, so long runs can report their progress.
+    ///
+    /// # Arguments
+    ///
+    /// - `times`: number of cycles as a usize.
+    /// - `report_every`: how many cycles to run between calls to `f`, 0 to never call it.
+    /// - `f`: progress callback.
+    pub fn cycle_brute_force_with_progress(&self, times: usize, report_every: usize, mut f: impl FnMut(usize)) -> Self {
+        let mut out: TiltingPlatform = self.clone();
+        for iteration in 1..=times {
+            out.spin_mut();
+            if report_every > 0 && iteration % report_every == 0 {
+                f(iteration);
+            }
+        }
+        out
+    }
+
+    /// This method produces the state after `times` cycles, but it keeps a list of states encountered to abort 
+    /// when a loop is detected and all possible configurations have been encountered. When that condition is met 
+    /// the previously calculated state that lines up with the remaining number of iterations is returned, if no 
+    /// loop is found by the time the iterations are exhausted, this function operates essentialy like the brute 
+    /// force version. Seen states are indexed by a `HashMap` so each lookup is constant time rather than a scan
+    /// over every previous state, and platforms that fit in a `BitGrid` are spun on their bitset form.
+    /// 
+    /// # Arguments
+    /// 
+    /// - `times`: number of cycles as a usize.
+    pub fn cycle(&self, times: usize) -> Self {
+        self.cycle_with_order(times, &CycleOrder::default())
+    }
+
+    /// Same as `cycle`, but with every spin cycle tilting towards the directions of `order` instead of North,
+    /// West, South and East.
+    ///
+    /// # Arguments
+    ///
+    /// - `times`: number of cycles as a usize.
+    /// - `order`: the directions of a single spin cycle, in the order they are tilted towards.
+    pub fn cycle_with_order(&self, times: usize, order: &CycleOrder) -> Self {
+        match BitGrid::from_matrix(&self.matrix) {
+            Some(bits) => TiltingPlatform::from(
+                find_cycle_state(bits.clone(), times, bits.cycles_with(order.0)).to_matrix()
+            ),
+            None => find_cycle_state(self.clone(), times, self.cycles_with(order.0))
+        }
+    }
+
+    /// Same as `cycle`, but storing at most `max_states` states, the platform itself included, while looking for
+    /// the loop. If neither the loop nor the state after `times` cycles is reached by then a `CycleError` is
+    /// returned, which bounds the memory a platform with a huge orbit can take.
+    ///
+    /// # Arguments
+    ///
+    /// - `times`: number of cycles as a usize.
+    /// - `max_states`: how many states may be stored at most.
+    pub fn cycle_capped(&self, times: usize, max_states: usize) -> Result<Self, CycleError> {
+        match BitGrid::from_matrix(&self.matrix) {
+            Some(bits) => find_cycle_state_capped(bits.clone(), times, bits.cycles(), max_states)
+                .map(|state| TiltingPlatform::from(state.to_matrix())),
+            None => find_cycle_state_capped(self.clone(), times, self.cycles(), max_states)
+        }
+        .ok_or(CycleError { max_states })
+    }
+
+    /// Runs `cycle(times)` on each of `platforms`, each with its own loop detection, returning the results in the
+    /// same order.
+    #[cfg(not(feature = "parallel"))]
+    pub fn cycle_many(platforms: &[TiltingPlatform], times: usize) -> Vec<TiltingPlatform> {
+        platforms.iter().map(|platform| platform.cycle(times)).collect()
+    }
+
+    /// Runs `cycle(times)` on each of `platforms`, each with its own loop detection, returning the results in the
+    /// same order. The platforms are split into one contiguous chunk per available core and each chunk is spun on
+    /// its own thread.
+    #[cfg(feature = "parallel")]
+    pub fn cycle_many(platforms: &[TiltingPlatform], times: usize) -> Vec<TiltingPlatform> {
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let chunk_len = platforms.len().div_ceil(threads).max(1);
+        thread::scope(|scope| {
+            platforms
+                .chunks(chunk_len)
+                .map(|chunk| scope.spawn(move || {
+                    chunk.iter().map(|platform| platform.cycle(times)).collect::<Vec<TiltingPlatform>>()
+                }))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("A cycle_many worker panicked!"))
+                .collect()
+        })
+    }
+
+    /// Spins the platform until a state repeats and reports the shape of the sequence of states: how many cycles
+    /// run before the loop is entered, how long the loop is and the load of every distinct state, starting with
+    /// the platform itself.
+    pub fn detect_cycle(&self) -> CycleInfo {
+        let (loads, prefix_len) = self.orbit_loads(usize::MAX);
+        CycleInfo::new(loads, prefix_len)
+    }
+
+    /// Cells some round stone can be brought to by some sequence of tilts, as a mask with one `bool` per cell. Every
+    /// state reachable from the platform by tilting in any order is explored, so the cells left `false` are the
+    /// ones no stone ever gets to, square stones included. The number of such states can grow quickly with the
+    /// number of round stones.
+    pub fn reachable_cells(&self) -> Vec<Vec<bool>> {
+        let (rows, cols) = self.dimensions();
+        let mut reachable = vec![vec![false; cols]; rows];
+        let mut seen: HashSet<TiltingPlatform> = HashSet::from([self.clone()]);
+        let mut pending: Vec<TiltingPlatform> = vec![self.clone()];
+        while let Some(state) = pending.pop() {
+            state.round_positions().for_each(|(row, col)| reachable[row][col] = true);
+            for direction in SPIN_CYCLE.iter() {
+                let next = state.tilt(direction);
+                if seen.insert(next.clone()) {
+                    pending.push(next);
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Answers to both parts of the puzzle as `(part1, part2)`: the load after a single North tilt and the load after
+    /// a billion spin cycles.
+    pub fn solve_both(&self) -> (usize, usize) {
+        (self.tilt(&Direction::North).get_load(), self.cycle(1_000_000_000).get_load())
+    }
+
+    /// Number of distinct states the platform goes through when spun over and over, itself included: the cycles
+    /// before the loop plus the length of the loop.
+    pub fn orbit_size(&self) -> usize {
+        let info = self.detect_cycle();
+        info.prefix_len + info.loop_len
+    }
+
+    /// Number of spin cycles `cycle` runs before it meets a state it has seen before, `prefix_len + loop_len` of
+    /// `detect_cycle`. Every state before the repeat is distinct, so this is also `orbit_size`.
+    pub fn steps_to_repeat(&self) -> usize {
+        self.orbit_size()
+    }
+
+    /// Smallest number of spin cycles, at most `max`, after which the load of the platform is `target`, counting
+    /// the platform itself as 0 cycles. Only the orbit is walked, since every later state repeats one of it, so
+    /// `None` means the load is never reached within `max` cycles.
+    ///
+    /// # Arguments
+    ///
+    /// - `target`: the load to look for.
+    /// - `max`: largest number of cycles to consider.
+    pub fn cycles_to_reach_load(&self, target: usize, max: usize) -> Option<usize> {
+        self.orbit_loads(max).0.iter().position(|load| *load == target)
+    }
+
+    /// Smallest and largest load among the states of the loop the platform ends up in, together with the length of
+    /// the loop, as `(min, max, loop_len)`. The states visited before the loop is entered are not considered.
+    pub fn load_extremes(&self) -> (usize, usize, usize) {
+        let info = self.detect_cycle();
+        let loop_loads = &info.loads[info.prefix_len..];
+        (
+            *loop_loads.iter().min().expect("A loop always has at least one state!"),
+            *loop_loads.iter().max().expect("A loop always has at least one state!"),
+            info.loop_len
+        )
+    }
+
+    /// Spins the platform at most `max` times watching only its load, and stops as soon as the last four stretches
+    /// of `period` loads are the same, for the smallest such `period`. Returns that period, or 0 if the loads did
+    /// not settle within `max` cycles, together with the load after each cycle run, `loads[k]` being the load after
+    /// `k + 1` cycles. Only loads are stored, which is cheaper than `detect_cycle`, but two different states can
+    /// have the same load, so the period is a guess that can be shorter than the real loop.
+    ///
+    /// # Arguments
+    ///
+    /// - `max`: largest number of cycles to run.
+    pub fn cycle_until_load_cycle(&self, max: usize) -> (usize, Vec<usize>) {
+        let mut loads: Vec<usize> = vec![];
+        // matching[p - 1] is how many of the latest loads in a row equal the load `p` cycles before them.
+        let mut matching: Vec<usize> = vec![];
+        for state in self.cycles().take(max) {
+            loads.push(state.get_load());
+            let last = loads.len() - 1;
+            matching.push(0);
+            for (p, run) in (1..=last).zip(matching.iter_mut()) {
+                *run = if loads[last] == loads[last - p] { *run + 1 } else { 0 };
+            }
+            if let Some(period) = (1..=loads.len() / 4).find(|p| matching[p - 1] >= 3 * p) {
+                return (period, loads);
+            }
+        }
+        (0, loads)
+    }
+
+    /// Loads of the distinct states met over at most `limit` spin cycles, starting with the platform itself,
+    /// together with the index where the loop starts if a state repeated within `limit` cycles.
+    fn orbit_loads(&self, limit: usize) -> (Vec<usize>, Option<usize>) {
+        match BitGrid::from_matrix(&self.matrix) {
+            Some(bits) => {
+                let (states, prefix_len) = walk_orbit(bits.clone(), bits.cycles(), limit);
+                (states.iter().map(BitGrid::get_load).collect(), prefix_len)
+            },
+            None => {
+                let (states, prefix_len) = walk_orbit(self.clone(), self.cycles(), limit);
+                (states.iter().map(TiltingPlatform::get_load).collect(), prefix_len)
+            }
+        }
+    }
+
+    /// Load after each of the first `cycles` spin cycles, `history[k]` being the load after `k + 1` cycles. New
+    /// states are only simulated until the loop is found, the rest of the history is read off the loop.
+    pub fn load_history(&self, cycles: usize) -> Vec<usize> {
+        let (loads, prefix_len) = self.orbit_loads(cycles);
+        (1..=cycles)
+            .map(|cycle| match prefix_len {
+                Some(prefix_len) => loads[orbit_index(prefix_len, loads.len() - prefix_len, cycle)],
+                None => loads[cycle]
+            })
+            .collect()
+    }
+
+    /// Lazily yields the platform after each full spin cycle, starting with the state after the first one. The
+    /// iterator never ends, so combine it with `take`, `nth` or similar adapters.
+    pub fn cycles(&self) -> impl Iterator<Item = TiltingPlatform> {
+        self.cycles_with(SPIN_CYCLE)
+    }
+
+    fn cycles_with(&self, order: [Direction; 4]) -> impl Iterator<Item = TiltingPlatform> {
+        let mut state = self.clone();
+        iter::from_fn(move || {
+            state.tilt_sequence_mut(&order);
+            Some(state.clone())
+        })
+    }
+
+    /// Writes a frame to `out` after every tilt of up to `cycles` spin cycles, so the stones can be watched as
+    /// they settle. The animation stops early as soon as a spin cycle produces a state seen before, which covers
+    /// both a platform that no longer changes and one that has entered a loop. Returns the number of spin cycles
+    /// that were animated.
+    ///
+    /// # Arguments
+    ///
+    /// - `cycles`: maximum number of spin cycles to animate.
+    /// - `out`: sink the frames are written to.
+    /// - `options`: whether to clear the terminal between frames and how long to wait after each one.
+    pub fn animate<W: io::Write>(&self, cycles: usize, out: &mut W, options: &AnimateOptions) -> io::Result<usize> {
+        let mut state = self.clone();
+        let mut seen: HashSet<TiltingPlatform> = HashSet::from([self.clone()]);
+        for cycle in 1..=cycles {
+            for direction in SPIN_CYCLE.iter() {
+                state.tilt_mut(direction);
+                if options.clear {
+                    write!(out, "\x1b[2J\x1b[H")?;
+                }
+                writeln!(out, "Cycle {}, {:?}\n{}\n", cycle, direction, state)?;
+                out.flush()?;
+                if !options.delay.is_zero() {
+                    thread::sleep(options.delay);
+                }
+            }
+            if !seen.insert(state.clone()) {
+                return Ok(cycle);
+            }
+        }
+        Ok(cycles)
+    }
+
+    pub fn to_str(&self) -> String {
+        self.to_string()
+    }
+
+    /// Renders the platform as a binary P6 PPM image where every cell is a `cell_size` pixels wide square, empty
+    /// cells drawn light grey, round stones orange and square stones dark grey.
+    ///
+    /// # Arguments
+    ///
+    /// - `cell_size`: side of the square drawn for each cell, in pixels.
+    pub fn render_ppm(&self, cell_size: usize) -> Vec<u8> {
+        let (rows, cols) = self.dimensions();
+        let mut image = format!("P6\n{} {}\n255\n", cols * cell_size, rows * cell_size).into_bytes();
+        for row in self.matrix.iter() {
+            let line: Vec<u8> = row
+                .iter()
+                .flat_map(|space| {
+                    let color: [u8; 3] = match space {
+                        TPSpace::Empty => [0xdd, 0xdd, 0xdd],
+                        TPSpace::RoundStone => [0xe0, 0x80, 0x20],
+                        TPSpace::SquareStone => [0x40, 0x40, 0x40]
+                    };
+                    color.repeat(cell_size)
+                })
+                .collect();
+            for _ in 0..cell_size {
+                image.extend_from_slice(&line);
+            }
+        }
+        image
+    }
+
+    /// 64 bit FNV-1a hash of the `snapshot` of the platform. Unlike `Hash` the value does not depend on the
+    /// hasher or the run, so it can be logged and compared across runs. Equal platforms always get the same
+    /// fingerprint, its dimensions included.
+    pub fn fingerprint(&self) -> u64 {
+        self.snapshot()
+            .iter()
+            .fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+    }
+
+    /// Packs the platform into its two bits per cell form, see `PackedPlatform`.
+    pub fn pack(&self) -> PackedPlatform {
+        PackedPlatform::from_matrix(&self.matrix)
+    }
+
+    /// Compact binary snapshot of the platform: the number of rows and columns as little endian `u32`s, followed by
+    /// the cells in row-major order packed four to a byte, two bits each and lowest bits first.
+    fn snapshot(&self) -> Vec<u8> {
+        let (rows, cols) = self.dimensions();
+        let mut bytes: Vec<u8> = Vec::with_capacity(8 + (rows * cols).div_ceil(4));
+        bytes.extend((rows as u32).to_le_bytes());
+        bytes.extend((cols as u32).to_le_bytes());
+        for (i, space) in self.matrix.iter().flatten().enumerate() {
+            if i % 4 == 0 {
+                bytes.push(0);
+            }
+            let code = match space {
+                TPSpace::Empty => 0,
+                TPSpace::RoundStone => 1,
+                TPSpace::SquareStone => 2
+            };
+            *bytes.last_mut().unwrap() |= code << (2 * (i % 4));
+        }
+        bytes
+    }
+
+    /// Encodes the platform with bincode, through its serde derives, so it can be stored and read back with
+    /// `from_bytes`.
+    #[cfg(feature = "bincode")]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serde::encode_to_vec(self, bincode::config::standard()).expect("Platforms can always be encoded")
+    }
+
+    /// Decodes a platform encoded by `to_bytes`. Trailing bytes after the platform are rejected, and so are rows of
+    /// different widths, like `parse` does.
+    #[cfg(feature = "bincode")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, bincode::error::DecodeError> {
+        let (platform, read) = bincode::serde::decode_from_slice(bytes, bincode::config::standard())?;
+        if read != bytes.len() {
+            return Err(bincode::error::DecodeError::Other("Trailing bytes after the platform!"));
+        }
+        Ok(platform)
+    }
+
+    /// Encodes the platform on a single line as its size followed by its rows joined by `/`, like
+    /// `5x8:..#..O../..#O..../...`, which is handy for appending many states to a line oriented log.
+    pub fn to_compact(&self) -> String {
+        let (rows, cols) = self.dimensions();
+        let body = self.matrix
+            .iter()
+            .map(|row| row.iter().map(|space| CharMap::default().glyph(*space)).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("/");
+        format!("{}x{}:{}", rows, cols, body)
+    }
+
+    /// Decodes a platform written by `to_compact`, checking that the rows add up to the declared size. Platforms
+    /// with rows but no columns come back with as many empty rows as the header declares.
+    pub fn from_compact(s: &str) -> Result<Self, CompactError> {
+        let (size, body) = s.trim_end().split_once(':').ok_or(CompactError::InvalidHeader)?;
+        let (rows, cols) = size.split_once('x').ok_or(CompactError::InvalidHeader)?;
+        let expected: (usize, usize) = (
+            rows.parse().map_err(|_| CompactError::InvalidHeader)?,
+            cols.parse().map_err(|_| CompactError::InvalidHeader)?
+        );
+        let platform = if expected.1 == 0 && body.chars().all(|c| c == '/') {
+            // Empty rows are blank lines, which `parse` drops, so they are counted off the separators instead. An
+            // empty body is a single empty row unless the header says there are no rows at all.
+            let rows = match body.is_empty() {
+                true => expected.0.min(1),
+                false => body.len() + 1
+            };
+            TiltingPlatform::from(vec![vec![]; rows])
+        } else {
+            TiltingPlatform::parse(body.split('/').map(String::from).collect())?
+        };
+        let found = platform.dimensions();
+        if found != expected {
+            return Err(CompactError::Dimensions(DimensionError { expected, found }));
+        }
+        Ok(platform)
+    }
+
+    /// Renders the platform like `Display` does, but with every cell that differs from `other` drawn as an `X`.
+    /// Both platforms must have the same dimensions.
+    pub fn diff(&self, other: &Self) -> Result<String, DimensionError> {
+        self.check_dimensions(other)?;
+        Ok(self.matrix
+            .iter()
+            .zip(other.matrix.iter())
+            .map(|(row, other_row)| row
+                .iter()
+                .zip(other_row.iter())
+                .map(|(space, other_space)| match space == other_space {
+                    true => CharMap::default().glyph(*space),
+                    false => 'X'
+                })
+                .collect::<String>()
+            )
+            .collect::<Vec<String>>()
+            .join("\n"))
+    }
+
+    /// Number of cells that differ between the platform and `other`, which must have the same dimensions. A single
+    /// stone moving to another cell counts twice, once for the cell it left and once for the one it reached.
+    pub fn distance(&self, other: &Self) -> Result<usize, DimensionError> {
+        self.check_dimensions(other)?;
+        Ok(self.cells().zip(other.cells()).filter(|(a, b)| a != b).count())
+    }
+
+    fn check_dimensions(&self, other: &Self) -> Result<(), DimensionError> {
+        match (self.dimensions(), other.dimensions()) {
+            (expected, found) if expected != found => Err(DimensionError { expected, found }),
+            _ => Ok(())
+        }
+    }
+
+    /// Renders the platform like `Display` does, but with the glyphs of `map`.
+    pub fn render_with(&self, map: &CharMap) -> String {
+        let mut out = String::new();
+        self.write_with(&mut out, map).expect("Writing to a String cannot fail!");
+        out
+    }
+
+    fn write_with(&self, f: &mut impl Write, map: &CharMap) -> fmt::Result {
+        for (i, row) in self.matrix.iter().enumerate() {
+            if i > 0 {
+                f.write_char('\n')?;
+            }
+            for space in row {
+                f.write_char(map.glyph(*space))?;
+            }
+        }
+        Ok(())
+    }
+
+}
+
+impl PartialEq for TiltingPlatform {
+
+    fn eq(&self, other: &Self) -> bool {
+        self.matrix == other.matrix
+    }
+
+}
+
+impl Eq for TiltingPlatform {}
+
+impl Hash for TiltingPlatform {
+
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.matrix.hash(state);
+    }
+
+}
+
+/// Platforms are ordered by `get_load` first, so sorting puts the least top-heavy states first. Platforms with
+/// the same load are ordered by their cells, row by row, which keeps the order total and consistent with `Eq`.
+impl Ord for TiltingPlatform {
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.get_load().cmp(&other.get_load()).then_with(|| self.matrix.cmp(&other.matrix))
+    }
+
+}
+
+impl PartialOrd for TiltingPlatform {
+
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+
+}
+
+/// Cell at `(row, col)`, panicking when out of bounds like slice indexing does. See `get` for a checked version.
+impl Index<(usize, usize)> for TiltingPlatform {
+
+    type Output = TPSpace;
+
+    fn index(&self, (row, col): (usize, usize)) -> &TPSpace {
+        &self.matrix[row][col]
+    }
+
+}
+
+/// Mutable cell at `(row, col)`, panicking when out of bounds like slice indexing does. See `set` for a checked
+/// version.
+impl IndexMut<(usize, usize)> for TiltingPlatform {
+
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut TPSpace {
+        self.load_cache = None;
+        &mut self.matrix[row][col]
+    }
+
+}
+
+impl From<Vec<Vec<TPSpace>>> for TiltingPlatform {
+
+    /// Wraps `matrix` as a platform. The rows are expected to all have the same length, which is only checked in
+    /// debug builds; use `parse` for untrusted input.
+    fn from(matrix: Vec<Vec<TPSpace>>) -> Self {
+        debug_assert!(
+            matrix.iter().all(|row| row.len() == matrix[0].len()),
+            "All rows of a tilting platform must have the same length!"
+        );
+        TiltingPlatform { matrix, load_cache: None }
+    }
+
+}
+
+/// Shape of a serialized `TiltingPlatform`, which is only turned into one once its rows are known to be as wide as
+/// each other.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawPlatform {
+    matrix: Vec<Vec<TPSpace>>
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawPlatform> for TiltingPlatform {
+
+    type Error = ParseError;
+
+    fn try_from(raw: RawPlatform) -> Result<Self, ParseError> {
+        let expected = raw.matrix.first().map_or(0, Vec::len);
+        match raw.matrix.iter().position(|row| row.len() != expected) {
+            Some(row) => Err(ParseError::RaggedGrid { row, expected, found: raw.matrix[row].len() }),
+            None => Ok(TiltingPlatform::from(raw.matrix))
+        }
+    }
+
+}
+
+impl From<TiltingPlatform> for Vec<Vec<TPSpace>> {
+
+    fn from(platform: TiltingPlatform) -> Self {
+        platform.matrix
+    }
+
+}
+
+impl FromStr for TiltingPlatform {
+
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TiltingPlatform::parse(s.lines().map(String::from).collect())
+    }
+
+}
+
+impl fmt::Display for TiltingPlatform {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with(f, &CharMap::default())
+    }
+
+}
+
+/// Minimal xorshift64 generator backing `TiltingPlatform::random`.
+struct XorShift(u64);
+
+impl XorShift {
+
+    fn new(seed: u64) -> Self {
+        // Run the seed through a splitmix64 step so that 0 and other small seeds still give a busy state.
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        XorShift((z ^ (z >> 31)).max(1))
+    }
+
+    /// Uniform value in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+}
+
+/// The order the platform is tilted in during a single spin cycle.
+const SPIN_CYCLE: [Direction; 4] = [
+    Direction::North,
+    Direction::West,
+    Direction::South,
+    Direction::East
+];
+
+
+/// Smallest grid, in cells, whose row tilts are spread across threads with the `parallel` feature.
+#[cfg(feature = "parallel")]
+const PARALLEL_MIN_CELLS: usize = 1 << 16;
+
+/// Number of round stones in the outermost row or column on each side, as returned by
+/// `TiltingPlatform::edge_counts`. A stone in a corner counts for both of its edges.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct EdgeCounts {
+    pub north: usize,
+    pub south: usize,
+    pub east: usize,
+    pub west: usize
+}
+
+/// Directions a single spin cycle tilts the platform towards, in order, as taken by
+/// `TiltingPlatform::cycle_with_order`. The default is the puzzle's North, West, South and East.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct CycleOrder(pub [Direction; 4]);
+
+impl Default for CycleOrder {
+
+    fn default() -> Self {
+        CycleOrder(SPIN_CYCLE)
+    }
+
+}
+
+/// Start and end `(row, col)` of a round stone during a tilt, as returned by `TiltingPlatform::tilt_with_moves`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Move {
+    pub from: (usize, usize),
+    pub to: (usize, usize)
+}
+
+/// Run of cells between two square stones, or a square stone and the edge, along the line a tilt slides stones
+/// on, as returned by `TiltingPlatform::segments`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Segment {
+    /// Row of the segment for West and East tilts, column for North and South ones.
+    pub lane: usize,
+    /// Index within the lane of the first cell of the segment, counted from the North or West edge.
+    pub start: usize,
+    /// Number of cells in the segment.
+    pub len: usize,
+    /// Number of round stones in the segment, which is how many cells it ends up filling.
+    pub round: usize
+}
+
+/// How `TiltingPlatform::animate` presents its frames.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct AnimateOptions {
+    /// Clear the terminal with ANSI escapes before each frame.
+    pub clear: bool,
+    /// Time to wait after each frame.
+    pub delay: Duration
+}
+
+/// Shape of the sequence of states a platform goes through when spun over and over, as returned by
+/// `TiltingPlatform::detect_cycle`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CycleInfo {
+    /// Number of cycles before the first state of the loop is reached.
+    pub prefix_len: usize,
+    /// Number of cycles it takes the loop to come back to the same state.
+    pub loop_len: usize,
+    /// Load of each distinct state, `loads[k]` being the load after `k` cycles.
+    pub loads: Vec<usize>
+}
+
+impl CycleInfo {
+
+    fn new(loads: Vec<usize>, prefix_len: Option<usize>) -> Self {
+        let prefix_len = prefix_len.expect("The states of a platform always end up looping!");
+        CycleInfo { prefix_len, loop_len: loads.len() - prefix_len, loads }
+    }
+
+    /// Index into `loads` of the state reached after `cycle` cycles.
+    pub fn state_index(&self, cycle: usize) -> usize {
+        orbit_index(self.prefix_len, self.loop_len, cycle)
+    }
+
+    /// Load of the state reached after `cycle` cycles, read off `loads` without spinning anything.
+    pub fn load_at(&self, cycle: usize) -> usize {
+        self.loads[self.state_index(cycle)]
+    }
+
+}
+
+/// Answers repeated queries about the states a platform goes through when spun over and over. The orbit, every
+/// distinct state up to the first repeat, is only simulated on the first query and kept for the following ones,
+/// so each later query costs a lookup.
+#[derive(Debug, Clone)]
+pub struct SpinSolver {
+    start: TiltingPlatform,
+    orbit: Option<(Vec<TiltingPlatform>, usize)>
+}
+
+impl SpinSolver {
+
+    pub fn new(platform: TiltingPlatform) -> Self {
+        SpinSolver { start: platform, orbit: None }
+    }
+
+    /// State of the platform after `cycle` spin cycles.
+    pub fn state_at(&mut self, cycle: usize) -> TiltingPlatform {
+        self.state(cycle).clone()
+    }
+
+    /// Load of the platform after `cycle` spin cycles.
+    pub fn load_at(&mut self, cycle: usize) -> usize {
+        self.state(cycle).get_load()
+    }
+
+    fn state(&mut self, cycle: usize) -> &TiltingPlatform {
+        let start = &self.start;
+        let (states, prefix_len) = self.orbit.get_or_insert_with(|| {
+            let (states, prefix_len) = match BitGrid::from_matrix(&start.matrix) {
+                Some(bits) => {
+                    let (states, prefix_len) = walk_orbit(bits.clone(), bits.cycles(), usize::MAX);
+                    (states.iter().map(|state| TiltingPlatform::from(state.to_matrix())).collect(), prefix_len)
+                },
+                None => walk_orbit(start.clone(), start.cycles(), usize::MAX)
+            };
+            (states, prefix_len.expect("The states of a platform always end up looping!"))
+        });
+        &states[orbit_index(*prefix_len, states.len() - *prefix_len, cycle)]
+    }
+
+}
+
+/// Reusable harness for running many simulations from the same starting platform. It keeps the original around
+/// next to a working copy that `run` spins in place, and `reset` copies the original back over the working copy
+/// reusing its rows, so repeated runs do not reallocate the matrix.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    original: TiltingPlatform,
+    working: TiltingPlatform
+}
+
+impl Simulation {
+
+    pub fn new(platform: TiltingPlatform) -> Self {
+        Simulation { working: platform.clone(), original: platform }
+    }
+
+    /// Current state of the working copy.
+    pub fn state(&self) -> &TiltingPlatform {
+        &self.working
+    }
+
+    /// Restores the working copy to the original platform.
+    pub fn reset(&mut self) {
+        self.working.matrix.clone_from(&self.original.matrix);
+        self.working.load_cache = self.original.load_cache;
+    }
+
+    /// Runs `cycles` spin cycles on the working copy, one by one, and returns its load afterwards. Runs pick up
+    /// where the previous one left off unless `reset` is called in between.
+    pub fn run(&mut self, cycles: usize) -> usize {
+        (0..cycles).for_each(|_| self.working.spin_mut());
+        self.working.get_load()
+    }
+
+}
+
+/// Maps a cycle count onto the distinct states of an orbit that enters a loop of `loop_len` states after
+/// `prefix_len` cycles.
+fn orbit_index(prefix_len: usize, loop_len: usize, cycle: usize) -> usize {
+    if cycle < prefix_len + loop_len {
+        cycle
+    } else {
+        prefix_len + (cycle - prefix_len) % loop_len
+    }
+}
+
+/// Collects `start` followed by the first `limit` states of `spins`, the states after each spin cycle of `start`,
+/// stopping early as soon as a state repeats. Alongside the distinct states it returns the index where the
+/// repeated state first appeared, if one was found.
+fn walk_orbit<T: Clone + Eq + Hash>(start: T, spins: impl Iterator<Item = T>, limit: usize) -> (Vec<T>, Option<usize>) {
+    let mut seen: HashMap<T, usize> = HashMap::new();
+    seen.insert(start.clone(), 0);
+    let mut states: Vec<T> = vec![start];
+    for state in spins.take(limit) {
+        match seen.get(&state) {
+            Some(&i) => return (states, Some(i)),
+            None => {
+                seen.insert(state.clone(), states.len());
+                states.push(state);
+            }
+        }
+    }
+    (states, None)
+}
+
+/// Produces the state after `times` cycles out of `start` and `spins`, the states after each of its spin cycles,
+/// skipping the remaining spins as soon as the states start repeating.
+fn find_cycle_state<T: Clone + Eq + Hash>(start: T, times: usize, spins: impl Iterator<Item = T>) -> T {
+    find_cycle_state_capped(start, times, spins, usize::MAX).expect("Ran out of states before running out of cycles!")
+}
+
+/// Same as `find_cycle_state`, but giving up with `None` when `max_states` distinct states have been stored without
+/// finding the loop or reaching the state after `times` cycles.
+fn find_cycle_state_capped<T: Clone + Eq + Hash>(
+    start: T, times: usize, spins: impl Iterator<Item = T>, max_states: usize
+) -> Option<T> {
+    let (mut states, loop_start, unstored) = walk_orbit_capped(start, times, spins, max_states)?;
+    match (loop_start, unstored) {
+        (Some(prefix_len), _) => Some(states.swap_remove(orbit_index(prefix_len, states.len() - prefix_len, times))),
+        (None, Some(next)) if times == max_states => Some(next),
+        (None, None) if times < max_states => Some(states.swap_remove(times)),
+        _ => None
+    }
+}
+
+/// Same as `walk_orbit` for the first `times` spins, but storing at most `max_states` states, `start` included. Once
+/// that many are stored without a repeat, one more spin is run without storing it to tell whether it closes the
+/// loop, and if it does not it is returned alongside the stored states, since it is the state after `max_states`
+/// spins.
+fn walk_orbit_capped<T: Clone + Eq + Hash>(
+    start: T, times: usize, mut spins: impl Iterator<Item = T>, max_states: usize
+) -> Option<(Vec<T>, Option<usize>, Option<T>)> {
+    let (states, loop_start) = walk_orbit(start, spins.by_ref(), times.min(max_states.checked_sub(1)?));
+    if loop_start.is_some() || times < max_states {
+        return Some((states, loop_start, None));
+    }
+    let next = spins.next()?;
+    match states.iter().position(|state| *state == next) {
+        Some(prefix_len) => Some((states, Some(prefix_len), None)),
+        None => Some((states, None, Some(next)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::io::Cursor;
+    use std::iter;
+    use std::time::Instant;
+
+    use crate::bits::BitGrid;
+    use crate::walk_orbit_capped;
+    use crate::{AnimateOptions, CharMap, CompactError, CycleError, CycleInfo, CycleOrder, DimensionError, Direction, EdgeCounts, LoadError, Move, PackedPlatform, ParseError, RegionError, Segment, Simulation, SpinSolver, TPSpace, TiltingPlatform, SPIN_CYCLE};
+
+    fn random_platform(rows: usize, cols: usize, seed: u64) -> TiltingPlatform {
+        TiltingPlatform::random(rows, cols, 1.0 / 3.0, 1.0 / 3.0, seed)
+    }
+
+    fn tilt_rotating(platform: &TiltingPlatform, direction: &Direction) -> TiltingPlatform {
+        let rotate = match direction {
+            Direction::West => 0,
+            Direction::South => 1,
+            Direction::East => 2,
+            Direction::North => 3
+        };
+        let mut matrix = TiltingPlatform::rotate_matrix(&platform.matrix, rotate);
+        matrix.iter_mut().for_each(|row| TiltingPlatform::tilt_row(row));
+        TiltingPlatform::from(TiltingPlatform::rotate_matrix(&matrix, 4 - rotate))
+    }
+
+    fn tilt_bitset(platform: &TiltingPlatform, direction: &Direction) -> TiltingPlatform {
+        TiltingPlatform::from(BitGrid::from_matrix(&platform.matrix).unwrap().tilt(direction).to_matrix())
+    }
+
+    fn get_tp1() -> TiltingPlatform {
+        TiltingPlatform::from_lines(
+            &[
+                "..#..O..",
+                "..#O....",
+                "O.#.....",
+                "..#.O..O",
+                "..#.O.OO",
+            ]
+        ).unwrap()
+    }
+
+    fn get_tp2() -> TiltingPlatform {
+        TiltingPlatform::from_lines(
+            &[
+                "..O..#..",
+                "....O#..",
+                ".....#.O",
+                "O..O.#..",
+            ]
+        ).unwrap()
+    }
+
+    fn get_tp3() -> TiltingPlatform {
+        TiltingPlatform::from_lines(
+            &[
+                "..O...O",
+                "....O..",
+                "....O..",
+                ".O.....",
+                "#######",
+                "O..O...",
+            ]
+        ).unwrap()
+    }
+
+    fn get_tp4() -> TiltingPlatform {
+        TiltingPlatform::from_lines(
+            &[
+                "O..O...",
+                "#######",
+                ".O.....",
+                "....O..",
+                "....O..",
+                "..O...O",
+            ]
+        ).unwrap()
+    }
+
+    fn get_tp5() -> TiltingPlatform {
+        TiltingPlatform::from_lines(
+            &[
+                "O..O...",
+                "#######",
+                ".O.....",
+                "....O..",
+                "....O#.",
+                "..O...O",
+            ]
+        ).unwrap()
+    }
+
+    fn get_tp6() -> TiltingPlatform {
+        TiltingPlatform::from_lines(
+            &[
+                "O....#....",
+                "O.OO#....#",
+                ".....##...",
+                "OO.#O....O",
+                ".O.....O#.",
+                "O.#..O.#.#",
+                "..O..#O..O",
+                ".......O..",
+                "#....###..",
+                "#OO..#....",
+            ]
+        ).unwrap()
+    }
+
+    /// The small hand written platforms most tests run over.
+    fn fixtures() -> Vec<TiltingPlatform> {
+        vec![get_tp1(), get_tp2(), get_tp3(), get_tp4(), get_tp5()]
+    }
+
+    #[test]
+    fn test_tilt_west() {
+        let test_platform = get_tp1();
+        let stepped_platform = test_platform.tilt(&Direction::West);
+        println!("{}", test_platform.to_str());
+        println!("{}", stepped_platform.to_str());
+        assert_eq!(
+            stepped_platform,
+            TiltingPlatform::from_lines(
+                &[
+                    "..#O....",
+                    "..#O....",
+                    "O.#.....",
+                    "..#OO...",
+                    "..#OOO..",
+                ]
+            ).unwrap()
+        )
+    }
+
+    #[test]
+    fn test_tilt_east() {
+        let test_platform = get_tp2();
+        let stepped_platform = test_platform.tilt(&Direction::East);
+        println!("{}", test_platform.to_str());
+        println!("{}", stepped_platform.to_str());
+        assert_eq!(
+            stepped_platform,
+            TiltingPlatform::from_lines(
+                &[
+                    "....O#..",
+                    "....O#..",
+                    ".....#.O",
+                    "...OO#..",
+                ]
+            ).unwrap()
+        )
+    }
+
+    #[test]
+    fn test_tilt_south() {
+        let test_platform = get_tp3();
+        let stepped_platform = test_platform.tilt(&Direction::South);
+        println!("{}", test_platform.to_str());
+        println!("{}", stepped_platform.to_str());
+        assert_eq!(
+            stepped_platform,
+            TiltingPlatform::from_lines(
+                &[
+                    ".......",
+                    ".......",
+                    "....O..",
+                    ".OO.O.O",
+                    "#######",
+                    "O..O...",
+                ]
+            ).unwrap()
+        )
+    }
+
+    #[test]
+    fn test_tilt_north() {
+        let test_platform = get_tp4();
+        let stepped_platform = test_platform.tilt(&Direction::North);
+        println!("{}", test_platform.to_str());
+        println!("{}", stepped_platform.to_str());
+        assert_eq!(
+            stepped_platform,
+            TiltingPlatform::from_lines(
+                &[
+                    "O..O...",
+                    "#######",
+                    ".OO.O.O",
+                    "....O..",
+                    ".......",
+                    ".......",
+                ]
+            ).unwrap()
+        )
+    }
+
+    #[test]
+    fn test_cycle() {
+        let test_platform = get_tp5();
+        let stepped_platform = test_platform.cycle_brute_force(100);
+        println!("{}", test_platform.to_str());
+        println!("{}", stepped_platform.to_str());
+        assert_eq!(
+            stepped_platform,
+            TiltingPlatform::from_lines(
+                &[
+                    ".....OO",
+                    "#######",
+                    ".......",
+                    "......O",
+                    "....O#.",
+                    "....OOO",
+                ]
+            ).unwrap()
+        )
+    }
+
+    #[test]
+    fn test_cycle_with_loop_detection() {
+        let test_platform = get_tp5();
+        let stepped_platform = test_platform.cycle(100);
+        println!("{}", test_platform.to_str());
+        println!("{}", stepped_platform.to_str());
+        assert_eq!(
+            stepped_platform,
+            TiltingPlatform::from_lines(
+                &[
+                    ".....OO",
+                    "#######",
+                    ".......",
+                    "......O",
+                    "....O#.",
+                    "....OOO",
+                ]
+            ).unwrap()
+        )
+    }
+
+    #[test]
+    fn test_cycle_small_counts() {
+        let test_platform = get_tp5();
+        for times in [1, 2] {
+            assert_eq!(
+                test_platform.cycle(times),
+                test_platform.cycle_brute_force(times)
+            )
+        }
+    }
+
+    #[test]
+    fn test_cycle_loop_boundary() {
+        // get_tp5 repeats the state after 2 cycles on the 4th cycle, so 4 is the first count that hits the
+        // loop detection and 5 is the first that has to wrap around it.
+        let test_platform = get_tp5();
+        for times in [3, 4, 5, 6] {
+            assert_eq!(
+                test_platform.cycle(times),
+                test_platform.cycle_brute_force(times)
+            )
+        }
+    }
+
+    #[test]
+    fn test_cycle_single_state_loop() {
+        let test_platform = TiltingPlatform::from_lines(
+            &[
+                "..#..",
+                ".....",
+                "#...#",
+            ]
+        ).unwrap();
+        for times in [1, 2, 3, 10] {
+            assert_eq!(test_platform.cycle(times), test_platform)
+        }
+    }
+
+    #[test]
+    fn test_bitset_tilt_matches_matrix_tilt() {
+        let test_platform = TiltingPlatform::load("input.txt").unwrap();
+        for direction in SPIN_CYCLE {
+            assert_eq!(
+                tilt_bitset(&test_platform, &direction),
+                test_platform.tilt(&direction)
+            )
+        }
+    }
+
+    #[test]
+    fn test_tilt_wider_than_bitset() {
+        let test_platform = TiltingPlatform::parse(
+            vec![
+                format!("O{}#.O", ".".repeat(130)),
+                format!("{}O.", ".".repeat(132)),
+            ]
+        ).unwrap();
+        assert_eq!(
+            test_platform.tilt(&Direction::East),
+            TiltingPlatform::parse(
+                vec![
+                    format!("{}O#.O", ".".repeat(130)),
+                    format!("{}O", ".".repeat(133)),
+                ]
+            ).unwrap()
+        );
+        assert_eq!(
+            test_platform.tilt(&Direction::North),
+            TiltingPlatform::parse(
+                vec![
+                    format!("O{}#OO", ".".repeat(130)),
+                    ".".repeat(134),
+                ]
+            ).unwrap()
+        )
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture` to compare the bitset and matrix paths on the
+    /// 100x100 puzzle input, both for a single tilt and for whole spin cycles.
+    #[test]
+    #[ignore]
+    fn bench_bitset_tilt() {
+        let test_platform = TiltingPlatform::load("input.txt").unwrap();
+        let rounds = 1_000;
+
+        let start = Instant::now();
+        for _ in 0..rounds {
+            test_platform.tilt(&Direction::North);
+        }
+        let matrix_time = start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..rounds {
+            tilt_bitset(&test_platform, &Direction::North);
+        }
+        let bitset_time = start.elapsed();
+
+        let start = Instant::now();
+        let mut matrix_state = test_platform.clone();
+        for _ in 0..rounds {
+            SPIN_CYCLE.iter().for_each(|direction| matrix_state.tilt_mut(direction));
+        }
+        let matrix_spin_time = start.elapsed();
+
+        let start = Instant::now();
+        let mut bitset_state = BitGrid::from_matrix(&test_platform.matrix).unwrap();
+        for _ in 0..rounds {
+            bitset_state = SPIN_CYCLE.iter().fold(bitset_state, |out, direction| out.tilt(direction));
+        }
+        let bitset_spin_time = start.elapsed();
+
+        assert_eq!(bitset_state.to_matrix(), matrix_state.matrix);
+        println!("matrix tilt: {:?} per tilt", matrix_time / rounds);
+        println!("bitset tilt: {:?} per tilt", bitset_time / rounds);
+        println!("matrix spin: {:?} per cycle", matrix_spin_time / rounds);
+        println!("bitset spin: {:?} per cycle", bitset_spin_time / rounds);
+    }
+
+    #[test]
+    fn test_tilt_mut() {
+        for test_platform in fixtures() {
+            for direction in SPIN_CYCLE {
+                let mut stepped_platform = test_platform.clone();
+                stepped_platform.tilt_mut(&direction);
+                assert_eq!(stepped_platform, tilt_bitset(&test_platform, &direction))
+            }
+        }
+    }
+
+    #[test]
+    fn test_column_tilt_matches_rotation() {
+        for seed in 1..=20 {
+            let test_platform = random_platform(1 + seed as usize % 9, 1 + seed as usize * 7 % 11, seed);
+            for direction in SPIN_CYCLE {
+                assert_eq!(
+                    test_platform.tilt(&direction),
+                    tilt_rotating(&test_platform, &direction)
+                )
+            }
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let test_platform = get_tp2();
+        assert_eq!(
+            format!("{}", test_platform),
+            "..O..#..\n....O#..\n.....#.O\nO..O.#.."
+        );
+        assert_eq!(test_platform.to_str(), test_platform.to_string())
+    }
+
+    #[test]
+    fn test_from_str() {
+        let test_platform: TiltingPlatform = "..O..#..\r\n....O#..\n.....#.O\nO..O.#..\n".parse().unwrap();
+        assert_eq!(test_platform, get_tp2())
+    }
+
+    #[test]
+    fn test_parse_invalid_char() {
+        assert_eq!(
+            "..O.\n.#x.".parse::<TiltingPlatform>(),
+            Err(ParseError::InvalidChar { row: 1, col: 2, found: 'x' })
+        )
+    }
+
+    #[test]
+    fn test_parse_crlf() {
+        let test_platform = TiltingPlatform::parse(
+            vec![
+                String::from("..O..#..\r"),
+                String::from("....O#..\r"),
+                String::from(".....#.O  \r"),
+                String::from("O..O.#..\r"),
+            ]
+        ).unwrap();
+        assert_eq!(test_platform, get_tp2());
+        assert_eq!(
+            "..O..#..\r\n....O#..\r\n.....#.O\r\nO..O.#..\r\n".parse::<TiltingPlatform>().unwrap(),
+            get_tp2()
+        )
+    }
+
+    #[test]
+    fn test_parse_trailing_empty_line() {
+        let test_platform = TiltingPlatform::parse(
+            vec![
+                String::from("..O..#.."),
+                String::from("....O#.."),
+                String::from(".....#.O"),
+                String::from("O..O.#.."),
+                String::from(""),
+                String::from("\r"),
+            ]
+        ).unwrap();
+        assert_eq!(test_platform, get_tp2())
+    }
+
+    #[test]
+    fn test_parse_ragged_grid() {
+        assert_eq!(
+            TiltingPlatform::parse(
+                vec![
+                    String::from("..#..O.."),
+                    String::from("..#O...."),
+                    String::from("O.#.."),
+                    String::from("..#.O..O"),
+                ]
+            ),
+            Err(ParseError::RaggedGrid { row: 2, expected: 8, found: 5 })
+        )
+    }
+
+    #[test]
+    fn test_cycles() {
+        let test_platform = get_tp5();
+        let states: Vec<TiltingPlatform> = test_platform.cycles().take(10).collect();
+        for (i, state) in states.iter().enumerate() {
+            assert_eq!(*state, test_platform.cycle_brute_force(i + 1))
+        }
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_wasm_solve() {
+        let solve: fn(&str, u32) -> Result<u32, wasm_bindgen::JsError> = crate::wasm::solve;
+        assert_eq!(solve(&get_tp6().to_string(), 1_000_000_000).ok(), Some(64));
+        assert_eq!(crate::wasm::try_solve(&get_tp6().to_string(), 1_000_000_000), Ok(64));
+        assert!(crate::wasm::try_solve("..x", 1).is_err())
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_load_gzip() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(get_tp6().to_string().as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let loaded = TiltingPlatform::from_reader_decompressing(&compressed[..], false).unwrap();
+        assert_eq!(loaded, get_tp6());
+        let text = get_tp6().to_string();
+        assert_eq!(TiltingPlatform::from_reader_decompressing(text.as_bytes(), false).unwrap(), get_tp6());
+        assert!(TiltingPlatform::from_reader_decompressing(text.as_bytes(), true).is_err())
+    }
+
+    #[cfg(feature = "logging")]
+    #[test]
+    fn test_logging() {
+        use std::cell::Cell;
+
+        thread_local! {
+            static RECORDS: Cell<(usize, usize)> = const { Cell::new((0, 0)) };
+        }
+
+        // Counts the debug and trace records of the current thread only, as other tests tilt concurrently.
+        struct Counter;
+
+        impl log::Log for Counter {
+
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+
+            fn log(&self, record: &log::Record) {
+                RECORDS.with(|records| {
+                    let (debug, trace) = records.get();
+                    match record.level() {
+                        log::Level::Debug => records.set((debug + 1, trace)),
+                        log::Level::Trace => records.set((debug, trace + 1)),
+                        _ => ()
+                    }
+                })
+            }
+
+            fn flush(&self) {}
+
+        }
+
+        log::set_logger(&Counter).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+        // Two spin cycles on the bitset path, then one on the matrix path, four tilts each.
+        assert_eq!(get_tp6().cycle(2), get_tp6().cycle_brute_force(1).cycle_brute_force(1));
+        assert_eq!(RECORDS.with(Cell::get), (16, 16))
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        for mut test_platform in fixtures().into_iter().chain([get_tp6()]) {
+            test_platform.get_load_cached();
+            let json = serde_json::to_string(&test_platform).unwrap();
+            assert!(!json.contains("load_cache"));
+            assert_eq!(serde_json::from_str::<TiltingPlatform>(&json).unwrap(), test_platform)
+        }
+        let ragged = r#"{"matrix":[["Empty","Empty"],["RoundStone"]]}"#;
+        assert!(serde_json::from_str::<TiltingPlatform>(ragged).is_err())
+    }
+
+    #[test]
+    fn test_tilt_sequence() {
+        for test_platform in fixtures() {
+            assert_eq!(
+                test_platform.tilt_sequence(&[Direction::South, Direction::East]),
+                test_platform.tilt(&Direction::South).tilt(&Direction::East)
+            );
+            assert_eq!(test_platform.tilt_sequence(&SPIN_CYCLE), test_platform.spin());
+            assert_eq!(test_platform.tilt_sequence(&[]), test_platform)
+        }
+    }
+
+    #[test]
+    fn test_cycle_with_order() {
+        for test_platform in [get_tp1(), get_tp5(), get_tp6(), random_platform(9, 11, 4), random_platform(130, 3, 4)] {
+            assert_eq!(test_platform.cycle_with_order(1_000, &CycleOrder::default()), test_platform.cycle(1_000));
+            let order = CycleOrder([Direction::East, Direction::North, Direction::West, Direction::South]);
+            let mut expected = test_platform.clone();
+            (0..50).for_each(|_| expected.tilt_sequence_mut(&order.0));
+            assert_eq!(test_platform.cycle_with_order(50, &order), expected)
+        }
+    }
+
+    #[test]
+    fn test_spin() {
+        for test_platform in fixtures().into_iter().chain([get_tp6()]) {
+            assert_eq!(test_platform.spin(), test_platform.cycle_brute_force(1));
+            assert_eq!(test_platform.spin().spin(), test_platform.cycle(2))
+        }
+    }
+
+    #[test]
+    fn test_detect_cycle() {
+        let test_platform = get_tp5();
+        assert_eq!(
+            test_platform.detect_cycle(),
+            CycleInfo {
+                prefix_len: 2,
+                loop_len: 2,
+                loads: (0..4).map(|k| test_platform.cycle_brute_force(k).get_load()).collect()
+            }
+        )
+    }
+
+    #[test]
+    fn test_detect_cycle_single_state_loop() {
+        let test_platform = TiltingPlatform::from_lines(
+            &[
+                "..#..",
+                ".....",
+                "#...#",
+            ]
+        ).unwrap();
+        let info = test_platform.detect_cycle();
+        assert_eq!((info.prefix_len, info.loop_len), (0, 1));
+        assert_eq!(info.state_index(1_000_000_000), 0)
+    }
+
+    #[test]
+    fn test_reachable_cells() {
+        let test_platform = TiltingPlatform::from_lines(
+            &[
+                "O.#..",
+                "..#..",
+                "###..",
+            ]
+        ).unwrap();
+        assert_eq!(
+            test_platform.reachable_cells(),
+            vec![
+                vec![true, true, false, false, false],
+                vec![true, true, false, false, false],
+                vec![false, false, false, false, false],
+            ]
+        );
+        let reachable = get_tp1().reachable_cells();
+        assert!(get_tp1().round_positions().all(|(row, col)| reachable[row][col]));
+        assert!((0..5).all(|row| !reachable[row][2]))
+    }
+
+    #[test]
+    fn test_steps_to_repeat() {
+        for (test_platform, steps) in [(get_tp5(), 4), (get_tp6(), 10)] {
+            assert_eq!(test_platform.steps_to_repeat(), steps);
+            let states: Vec<TiltingPlatform> = iter::once(test_platform.clone())
+                .chain(test_platform.cycles())
+                .take(steps + 1)
+                .collect();
+            assert!(states[..steps].contains(&states[steps]));
+            assert_eq!(states[..steps].iter().collect::<HashSet<_>>().len(), steps)
+        }
+    }
+
+    #[test]
+    fn test_solve_both() {
+        assert_eq!(get_tp6().solve_both(), (136, 64))
+    }
+
+    #[test]
+    fn test_orbit_size() {
+        assert_eq!(get_tp5().orbit_size(), 4);
+        assert_eq!(get_tp6().orbit_size(), 10)
+    }
+
+    #[test]
+    fn test_load_at() {
+        for test_platform in [get_tp5(), get_tp6()] {
+            let info = test_platform.detect_cycle();
+            for cycle in 0..info.prefix_len {
+                assert_eq!(info.load_at(cycle), test_platform.cycle_brute_force(cycle).get_load())
+            }
+            for cycle in [1_000, 1_000_001, 1_000_000_000] {
+                assert_eq!(info.load_at(cycle), test_platform.cycle(cycle).get_load())
+            }
+        }
+        assert_eq!(get_tp6().detect_cycle().load_at(1_000_000_000), 64)
+    }
+
+    #[test]
+    fn test_cycle_capped() {
+        let test_platform = get_tp6();
+        assert_eq!(test_platform.cycle_capped(1_000_000_000, 10), Ok(test_platform.cycle(1_000_000_000)));
+        assert_eq!(test_platform.cycle_capped(5, 6), Ok(test_platform.cycle(5)));
+        assert_eq!(test_platform.cycle_capped(1_000_000_000, 9), Err(CycleError { max_states: 9 }));
+        assert_eq!(test_platform.cycle_capped(6, 3), Err(CycleError { max_states: 3 }));
+        // The state after `max_states` cycles is the spin that is run but not stored.
+        assert_eq!(test_platform.cycle_capped(5, 5), Ok(test_platform.cycle(5)));
+        assert_eq!(test_platform.cycle_capped(6, 5), Err(CycleError { max_states: 5 }));
+        for max_states in 0..=12 {
+            for times in [0, 5, 9, 10, 11, 1_000_000_000] {
+                let walk = walk_orbit_capped(test_platform.clone(), times, test_platform.cycles(), max_states);
+                if let Some((states, _, _)) = walk {
+                    assert!(states.len() <= max_states)
+                }
+                assert_eq!(
+                    test_platform.cycle_capped(times, max_states).is_ok(),
+                    max_states > times.min(9) || (max_states > 0 && times == max_states)
+                )
+            }
+        }
+    }
+
+    #[test]
+    fn test_simulation() {
+        let test_platform = get_tp6();
+        let mut simulation = Simulation::new(test_platform.clone());
+        for cycles in [3, 10, 3, 0, 25] {
+            simulation.reset();
+            assert_eq!(simulation.run(cycles), test_platform.cycle_brute_force(cycles).get_load());
+            assert_eq!(simulation.state(), &test_platform.cycle_brute_force(cycles))
+        }
+        simulation.reset();
+        simulation.run(2);
+        assert_eq!(simulation.run(3), test_platform.cycle_brute_force(5).get_load())
+    }
+
+    #[test]
+    fn test_spin_solver() {
+        for test_platform in [get_tp1(), get_tp5(), get_tp6(), random_platform(9, 11, 4)] {
+            let mut solver = SpinSolver::new(test_platform.clone());
+            for cycle in (0..40).chain([1_000, 999_999_999, 1_000_000_000]) {
+                assert_eq!(solver.state_at(cycle), test_platform.cycle(cycle));
+                if cycle < 40 {
+                    assert_eq!(solver.load_at(cycle), test_platform.cycle_brute_force(cycle).get_load())
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cycle_until_load_cycle() {
+        let test_platform = get_tp6();
+        let (period, loads) = test_platform.cycle_until_load_cycle(1_000);
+        assert_eq!(period, 7);
+        assert!(loads.len() < 1_000);
+        // The last `period` loads are those after cycles `first..=loads.len()`, later cycles repeat them.
+        let first = loads.len() - period + 1;
+        let load_at = |cycle: usize| loads[first - 1 + (cycle - first) % period];
+        for cycle in [loads.len(), 100, 1_000] {
+            assert_eq!(load_at(cycle), test_platform.cycle_brute_force(cycle).get_load())
+        }
+        assert_eq!(get_tp6().cycle_until_load_cycle(5).0, 0)
+    }
+
+    #[test]
+    fn test_cycles_to_reach_load() {
+        let test_platform = get_tp6();
+        assert_eq!(test_platform.cycles_to_reach_load(63, 1_000_000_000), Some(8));
+        assert_eq!(test_platform.cycles_to_reach_load(63, 8), Some(8));
+        assert_eq!(test_platform.cycles_to_reach_load(63, 7), None);
+        assert_eq!(test_platform.cycles_to_reach_load(87, 1_000), Some(1));
+        assert_eq!(test_platform.cycles_to_reach_load(test_platform.get_load(), 0), Some(0));
+        assert_eq!(test_platform.cycles_to_reach_load(1, 1_000_000_000), None)
+    }
+
+    #[test]
+    fn test_load_extremes() {
+        // Once in its loop, the load of the puzzle example goes through 69, 69, 65, 64, 65, 63 and 68.
+        assert_eq!(get_tp6().load_extremes(), (63, 69, 7))
+    }
+
+    #[test]
+    fn test_tilt_with_load() {
+        for test_platform in fixtures().into_iter().chain([random_platform(13, 17, 3)]) {
+            for direction in SPIN_CYCLE {
+                let (mut tilted, load) = test_platform.tilt_with_load(&direction);
+                assert_eq!(tilted, test_platform.tilt(&direction));
+                assert_eq!(load, tilted.get_load());
+                assert_eq!(tilted.get_load_cached(), load)
+            }
+        }
+        assert_eq!(get_tp6().tilt_with_load(&Direction::North).1, 136)
+    }
+
+    #[test]
+    fn test_tilt_checked() {
+        let test_platform = get_tp1();
+        let (tilted, moved) = test_platform.tilt_checked(&Direction::West);
+        assert_eq!((tilted.clone(), moved), (test_platform.tilt(&Direction::West), true));
+        assert_eq!(tilted.tilt_checked(&Direction::West), (tilted.clone(), false))
+    }
+
+    #[test]
+    fn test_settle_histogram() {
+        let test_platform = TiltingPlatform::from_lines(&["O.O", "#.O", "O.#", ".OO"]).unwrap();
+        assert_eq!(test_platform.settle_histogram(&Direction::North), vec![1, 1, 1, 2, 1]);
+        assert_eq!(test_platform.settle_histogram(&Direction::West), vec![2, 1, 1, 2]);
+        let tilted = test_platform.tilt(&Direction::North);
+        assert_eq!(tilted.rows()[0], vec![TPSpace::RoundStone, TPSpace::RoundStone, TPSpace::RoundStone]);
+        assert_eq!(tilted.get(1, 2), Some(TPSpace::RoundStone))
+    }
+
+    #[test]
+    fn test_max_slide() {
+        let test_platform = TiltingPlatform::from_lines(&["......O", "#...O.O"]).unwrap();
+        assert_eq!(test_platform.max_slide(&Direction::West), 6);
+        assert_eq!(test_platform.max_slide(&Direction::East), 1);
+        assert_eq!(test_platform.max_slide(&Direction::South), 0);
+        assert_eq!(test_platform.max_slide(&Direction::North), 1);
+        assert_eq!(test_platform.tilt(&Direction::West).max_slide(&Direction::West), 0);
+        assert_eq!(get_tp1().max_slide(&Direction::West), 3)
+    }
+
+    #[test]
+    fn test_is_settled() {
+        for test_platform in fixtures() {
+            for direction in SPIN_CYCLE {
+                assert_eq!(test_platform.is_settled(&direction), test_platform.tilt(&direction) == test_platform);
+                assert!(test_platform.tilt(&direction).is_settled(&direction))
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_settled_unsettled() {
+        let test_platform = TiltingPlatform::from_lines(
+            &[
+                "...",
+                ".O.",
+                "...",
+            ]
+        ).unwrap();
+        for direction in SPIN_CYCLE {
+            assert!(!test_platform.is_settled(&direction))
+        }
+    }
+
+    #[test]
+    fn test_drop_stone() {
+        let mut test_platform = get_tp1();
+        assert_eq!(test_platform.drop_stone(0, &Direction::North), Some((3, 0)));
+        assert_eq!(test_platform.drop_stone(0, &Direction::North), Some((4, 0)));
+        assert_eq!(test_platform.drop_stone(0, &Direction::North), None);
+        assert_eq!(test_platform.drop_stone(3, &Direction::North), Some((2, 3)));
+        assert_eq!(test_platform.drop_stone(1, &Direction::South), Some((4, 1)));
+        assert_eq!(test_platform.drop_stone(1, &Direction::West), Some((1, 4)));
+        assert_eq!(test_platform.drop_stone(0, &Direction::East), Some((0, 1)));
+        assert_eq!(test_platform.count_round(), get_tp1().count_round() + 6)
+    }
+
+    #[test]
+    fn test_drop_stone_full() {
+        let mut test_platform = get_tp1();
+        assert_eq!(test_platform.drop_stone(2, &Direction::North), None);
+        assert_eq!(test_platform.drop_stone(7, &Direction::North), None);
+        assert_eq!(test_platform.drop_stone(8, &Direction::North), None);
+        assert_eq!(test_platform.drop_stone(5, &Direction::West), None);
+        assert_eq!(test_platform, get_tp1())
+    }
+
+    #[test]
+    fn test_cells() {
+        let test_platform = get_tp1();
+        let cells: Vec<(usize, usize, TPSpace)> = test_platform.cells().collect();
+        assert_eq!(cells.len(), 5 * 8);
+        assert_eq!(&cells[..3], &[(0, 0, TPSpace::Empty), (0, 1, TPSpace::Empty), (0, 2, TPSpace::SquareStone)]);
+        assert_eq!(cells[8], (1, 0, TPSpace::Empty));
+        assert_eq!(
+            test_platform.round_positions().collect::<Vec<_>>(),
+            vec![(0, 5), (1, 3), (2, 0), (3, 4), (3, 7), (4, 4), (4, 6), (4, 7)]
+        );
+        assert_eq!(test_platform.round_positions().count(), test_platform.count_round())
+    }
+
+    #[test]
+    fn test_segments() {
+        let test_platform = TiltingPlatform::from_lines(&["O.#..O##O..#."]).unwrap();
+        assert_eq!(
+            test_platform.segments(&Direction::West),
+            vec![
+                Segment { lane: 0, start: 0, len: 2, round: 1 },
+                Segment { lane: 0, start: 3, len: 3, round: 1 },
+                Segment { lane: 0, start: 8, len: 3, round: 1 },
+                Segment { lane: 0, start: 12, len: 1, round: 0 },
+            ]
+        );
+        assert_eq!(test_platform.segments(&Direction::East), test_platform.segments(&Direction::West));
+        let columns = get_tp1().segments(&Direction::North);
+        assert_eq!(columns.len(), 7);
+        assert_eq!(columns[0], Segment { lane: 0, start: 0, len: 5, round: 1 });
+        assert_eq!(columns.iter().map(|s| s.round).sum::<usize>(), get_tp1().count_round())
+    }
+
+    #[test]
+    fn test_edge_counts() {
+        let test_platform = TiltingPlatform::from_lines(&["O.O.", "...O", "O#..", ".OOO"]).unwrap();
+        assert_eq!(test_platform.edge_counts(), EdgeCounts { north: 2, south: 3, east: 2, west: 2 });
+        assert_eq!(
+            get_tp1().tilt(&Direction::North).edge_counts(),
+            EdgeCounts { north: 6, south: 0, east: 2, west: 1 }
+        );
+        assert_eq!(
+            TiltingPlatform::from_lines(&["O"]).unwrap().edge_counts(),
+            EdgeCounts { north: 1, south: 1, east: 1, west: 1 }
+        )
+    }
+
+    #[test]
+    fn test_count_stones() {
+        let test_platform = get_tp5();
+        assert_eq!(test_platform.count_round(), 7);
+        assert_eq!(test_platform.count_square(), 8)
+    }
+
+    #[test]
+    fn test_tilt_preserves_round_count() {
+        for test_platform in fixtures() {
+            for direction in SPIN_CYCLE {
+                assert_eq!(test_platform.tilt(&direction).count_round(), test_platform.count_round())
+            }
+        }
+    }
+
+    #[test]
+    fn test_tilt_invariants_random() {
+        for seed in 1..=50 {
+            let test_platform = random_platform(1 + seed as usize % 13, 1 + seed as usize * 5 % 17, seed);
+            for direction in SPIN_CYCLE {
+                let stepped_platform = test_platform.tilt(&direction);
+                assert_eq!(stepped_platform.count_round(), test_platform.count_round());
+                for (row, stepped_row) in test_platform.matrix.iter().zip(stepped_platform.matrix.iter()) {
+                    for (space, stepped_space) in row.iter().zip(stepped_row.iter()) {
+                        assert_eq!(
+                            *space == TPSpace::SquareStone,
+                            *stepped_space == TPSpace::SquareStone
+                        )
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_dimensions() {
+        assert_eq!(get_tp1().dimensions(), (5, 8));
+        assert_eq!(get_tp3().dimensions(), (6, 7));
+        assert_eq!(TiltingPlatform::parse(vec![]).unwrap().dimensions(), (0, 0))
+    }
+
+    #[test]
+    fn test_empty_platform() {
+        let test_platform = TiltingPlatform::parse(vec![]).unwrap();
+        assert_eq!(test_platform.dimensions(), (0, 0));
+        assert_eq!(test_platform.get_load(), 0);
+        for direction in SPIN_CYCLE {
+            assert_eq!(test_platform.tilt(&direction), test_platform)
+        }
+        for quarter_turns in 0..4 {
+            assert_eq!(test_platform.rotate(quarter_turns), test_platform)
+        }
+        assert_eq!(test_platform.cycle(1_000_000_000), test_platform);
+        assert_eq!(test_platform.cycle_brute_force(3), test_platform);
+        assert_eq!(test_platform.transpose(), test_platform);
+        assert_eq!(test_platform.detect_cycle().loop_len, 1);
+        assert_eq!(test_platform.to_string(), "")
+    }
+
+    #[test]
+    fn test_get_set() {
+        let mut test_platform = get_tp1();
+        assert_eq!(test_platform.get(0, 2), Some(TPSpace::SquareStone));
+        assert_eq!(test_platform.get(0, 5), Some(TPSpace::RoundStone));
+        assert!(test_platform.set(0, 0, TPSpace::RoundStone));
+        assert_eq!(test_platform.get(0, 0), Some(TPSpace::RoundStone));
+        assert_eq!(
+            test_platform.tilt(&Direction::East).get(0, 1),
+            Some(TPSpace::RoundStone)
+        )
+    }
+
+    #[test]
+    fn test_get_set_out_of_bounds() {
+        let mut test_platform = get_tp1();
+        assert_eq!(test_platform.get(5, 0), None);
+        assert_eq!(test_platform.get(0, 8), None);
+        assert!(!test_platform.set(5, 0, TPSpace::RoundStone));
+        assert!(!test_platform.set(0, 8, TPSpace::RoundStone));
+        assert_eq!(test_platform, get_tp1())
+    }
+
+    #[test]
+    fn test_get_load_weighted() {
+        for test_platform in fixtures() {
+            assert_eq!(test_platform.get_load_weighted(1), test_platform.get_load());
+            assert_eq!(test_platform.get_load_weighted(2), 2 * test_platform.get_load())
+        }
+    }
+
+    #[test]
+    fn test_get_load_from() {
+        let test_platform = get_tp1();
+        assert_eq!(test_platform.get_load_from(&Direction::North), test_platform.get_load());
+        assert_eq!(test_platform.get_load_from(&Direction::West), test_platform.rotate(1).get_load());
+        assert_eq!(test_platform.get_load_from(&Direction::South), test_platform.rotate(2).get_load());
+        assert_eq!(test_platform.get_load_from(&Direction::East), test_platform.rotate(3).get_load())
+    }
+
+    #[test]
+    fn test_ord_by_load() {
+        let light: TiltingPlatform = "...\n...\nO..".parse().unwrap();
+        let heavy: TiltingPlatform = "O..\n...\n...".parse().unwrap();
+        let middle: TiltingPlatform = "...\n.O.\n...".parse().unwrap();
+        let middle_tie: TiltingPlatform = "...\nO..\n...".parse().unwrap();
+        let mut platforms = vec![heavy.clone(), middle.clone(), light.clone(), middle_tie.clone()];
+        platforms.sort();
+        assert_eq!(platforms, vec![light, middle, middle_tie, heavy.clone()]);
+        assert_eq!(platforms.iter().max(), Some(&heavy))
+    }
+
+    #[test]
+    fn test_index() {
+        let mut test_platform = get_tp1();
+        assert_eq!(test_platform[(0, 2)], TPSpace::SquareStone);
+        assert_eq!(test_platform[(0, 5)], TPSpace::RoundStone);
+        test_platform[(0, 0)] = TPSpace::RoundStone;
+        assert_eq!(test_platform.get(0, 0), Some(TPSpace::RoundStone))
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_out_of_bounds() {
+        let _ = get_tp1()[(5, 0)];
+    }
+
+    #[test]
+    fn test_get_load_cached() {
+        let mut test_platform = get_tp1();
+        let load = test_platform.get_load();
+        assert_eq!(test_platform.get_load_cached(), load);
+        assert_eq!(test_platform.get_load_cached(), load);
+        assert_eq!(test_platform, get_tp1());
+        test_platform.set(4, 0, TPSpace::RoundStone);
+        assert_eq!(test_platform.get_load_cached(), load + 1);
+        test_platform.tilt_mut(&Direction::North);
+        assert_eq!(test_platform.get_load_cached(), test_platform.get_load())
+    }
+
+    #[test]
+    fn test_get_load_cached_after_tilt_copies() {
+        let mut test_platform = TiltingPlatform::from_lines(&["...", "...", "O.."]).unwrap();
+        assert_eq!(test_platform.get_load_cached(), 1);
+        let (mut tilted, _) = test_platform.tilt_with_moves(&Direction::North);
+        assert_eq!(tilted.get_load_cached(), 3);
+        let (mut tilted, _) = test_platform.tilt_step(&Direction::North);
+        assert_eq!(tilted.get_load_cached(), 2);
+        let mut test_platform = TiltingPlatform::from_lines(&["#..", "...", "O.."]).unwrap();
+        assert_eq!(test_platform.get_load_cached(), 1);
+        let mut tilted = test_platform.tilt_wrapping(&Direction::North);
+        assert_eq!(tilted.get_load_cached(), 2);
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture`, with and without `--features parallel`, to
+    /// compare the sequential and threaded row tilts on a wide grid.
+    #[test]
+    #[ignore]
+    fn bench_row_tilt_wide() {
+        let test_platform = random_platform(2_000, 2_000, 42);
+        let rounds = 20;
+
+        let start = Instant::now();
+        let mut stepped_platform = test_platform.clone();
+        for _ in 0..rounds {
+            stepped_platform.tilt_mut(&Direction::West);
+            stepped_platform.tilt_mut(&Direction::East);
+        }
+        let row_time = start.elapsed();
+
+        println!("row tilt: {:?} per tilt", row_time / (2 * rounds));
+    }
+
+    #[test]
+    fn test_hash_set() {
+        let mut platforms = HashSet::new();
+        platforms.insert(get_tp1());
+        platforms.insert(get_tp1());
+        assert_eq!(platforms.len(), 1);
+        platforms.insert(get_tp2());
+        assert_eq!(platforms.len(), 2)
+    }
+
+    #[test]
+    fn test_rotate_counterclockwise() {
+        for (rows, cols) in [(1, 1), (1, 7), (7, 1), (5, 8), (13, 4)] {
+            let matrix = random_platform(rows, cols, 5).matrix;
+            let recursive = TiltingPlatform::rotate_matrix(&TiltingPlatform::rotate_matrix(&matrix, 1), 2);
+            assert_eq!(TiltingPlatform::rotate_matrix(&matrix, 3), recursive)
+        }
+    }
+
+    #[test]
+    fn test_pad() {
+        let test_platform = TiltingPlatform::from_lines(&["O#", ".O"]).unwrap().pad(1, 2, 3, 1);
+        assert_eq!(test_platform.dimensions(), (5, 6));
+        assert_eq!(test_platform.round_positions().collect::<Vec<_>>(), vec![(1, 3), (2, 4)]);
+        assert_eq!(test_platform.get(1, 4), Some(TPSpace::SquareStone));
+        assert_eq!(test_platform.count_square(), 1);
+        assert_eq!(get_tp1().pad(0, 0, 0, 0), get_tp1())
+    }
+
+    #[test]
+    fn test_subgrid() {
+        let test_platform = get_tp1();
+        assert_eq!(
+            test_platform.subgrid(1, 2, 3, 3).unwrap(),
+            TiltingPlatform::from_lines(&["#O.", "#..", "#.O"]).unwrap()
+        );
+        assert_eq!(test_platform.subgrid(0, 0, 5, 8), Ok(test_platform.clone()));
+        assert_eq!(test_platform.subgrid(5, 8, 0, 0).map(|p| p.dimensions()), Ok((0, 0)));
+        assert_eq!(
+            test_platform.subgrid(3, 6, 2, 3),
+            Err(RegionError { origin: (3, 6), size: (2, 3), dimensions: (5, 8) })
+        );
+        assert!(test_platform.subgrid(usize::MAX, 0, 2, 1).is_err())
+    }
+
+    #[test]
+    fn test_rotate() {
+        let test_platform = TiltingPlatform::from_lines(
+            &[
+                "O.#",
+                "..O",
+            ]
+        ).unwrap();
+        assert_eq!(
+            test_platform.rotate(1),
+            TiltingPlatform::from_lines(
+                &[
+                    ".O",
+                    "..",
+                    "O#",
+                ]
+            ).unwrap()
+        );
+        assert_eq!(test_platform.rotate(2), test_platform.rotate(1).rotate(1));
+        assert_eq!(test_platform.rotate(3), test_platform.rotate(2).rotate(1));
+        assert_eq!(test_platform.rotate(4), test_platform)
+    }
+
+    #[test]
+    fn test_transpose() {
+        let test_platform = get_tp2();
+        let transposed_platform = test_platform.transpose();
+        assert_eq!(transposed_platform.dimensions(), (8, 4));
+        assert_eq!(
+            transposed_platform,
+            TiltingPlatform::from_lines(
+                &[
+                    "...O",
+                    "....",
+                    "O...",
+                    "...O",
+                    ".O..",
+                    "####",
+                    "....",
+                    "..O.",
+                ]
+            ).unwrap()
+        );
+        assert_eq!(transposed_platform.transpose(), test_platform)
+    }
+
+    #[test]
+    fn test_cycle_matches_brute_force_random() {
+        for seed in 1..=4 {
+            let test_platform = random_platform(2 + seed as usize % 3, 2 + seed as usize * 3 % 4, seed);
+            for times in [1, 7, 50, 1_000, 1_000_000] {
+                assert_eq!(
+                    test_platform.cycle(times),
+                    test_platform.cycle_brute_force(times),
+                    "seed {} after {} cycles", seed, times
+                )
+            }
+        }
+    }
+
+    #[test]
+    fn test_animate() {
+        let test_platform = get_tp5();
+        let mut out: Vec<u8> = vec![];
+        let cycles = test_platform.animate(10, &mut out, &AnimateOptions::default()).unwrap();
+        let frames = String::from_utf8(out).unwrap();
+        // The state after 4 cycles repeats the one after 2, so the animation stops there.
+        assert_eq!(cycles, 4);
+        assert_eq!(frames.matches("Cycle ").count(), 16);
+        assert!(frames.starts_with(&format!("Cycle 1, North\n{}\n", test_platform.tilt(&Direction::North))));
+        assert!(frames.ends_with(&format!("Cycle 4, East\n{}\n\n", test_platform.cycle_brute_force(4))))
+    }
+
+    #[test]
+    fn test_load_history() {
+        let test_platform = get_tp5();
+        assert_eq!(
+            test_platform.load_history(20),
+            (1..=20).map(|k| test_platform.cycle_brute_force(k).get_load()).collect::<Vec<usize>>()
+        );
+        assert_eq!(test_platform.load_history(0), Vec::<usize>::new())
+    }
+
+    #[test]
+    fn test_tilt_to_equilibrium() {
+        for test_platform in fixtures() {
+            for direction in SPIN_CYCLE {
+                let (stable_platform, tilts) = test_platform.tilt_to_equilibrium(&direction);
+                assert_eq!(stable_platform, test_platform.tilt(&direction));
+                assert_eq!(tilts, usize::from(stable_platform != test_platform));
+                assert_eq!(stable_platform.tilt_to_equilibrium(&direction), (stable_platform.clone(), 0))
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_matrix() {
+        let matrix = vec![
+            vec![TPSpace::RoundStone, TPSpace::Empty, TPSpace::SquareStone],
+            vec![TPSpace::Empty, TPSpace::Empty, TPSpace::RoundStone],
+        ];
+        let test_platform = TiltingPlatform::from(matrix.clone());
+        assert_eq!(test_platform.rows(), matrix.as_slice());
+        assert_eq!(test_platform.to_string(), "O.#\n..O");
+        assert_eq!(Vec::<Vec<TPSpace>>::from(test_platform), matrix)
+    }
+
+    #[test]
+    fn test_column_loads() {
+        let test_platform = get_tp5();
+        assert_eq!(test_platform.column_loads(), vec![6, 4, 1, 6, 5, 0, 1]);
+        for test_platform in fixtures() {
+            assert_eq!(test_platform.column_loads().iter().sum::<usize>(), test_platform.get_load())
+        }
+    }
+
+    #[test]
+    fn test_cycle_brute_force_with_progress() {
+        let test_platform = get_tp5();
+        let mut reports: Vec<usize> = vec![];
+        let stepped_platform = test_platform.cycle_brute_force_with_progress(10, 3, |i| reports.push(i));
+        assert_eq!(reports, vec![3, 6, 9]);
+        assert_eq!(stepped_platform, test_platform.cycle_brute_force(10))
+    }
+
+    #[test]
+    fn test_char_map() {
+        let map = CharMap { empty: ' ', round: '@', square: '*' };
+        let test_platform = TiltingPlatform::parse_with(
+            vec![
+                String::from("  @  *  "),
+                String::from("    @*  "),
+                String::from("     * @"),
+                String::from("@  @ *  "),
+            ],
+            &map
+        ).unwrap();
+        assert_eq!(test_platform, get_tp2());
+        assert_eq!(test_platform.render_with(&map), "  @  *  \n    @*  \n     * @\n@  @ *  ");
+        assert_eq!(test_platform.render_with(&CharMap::default()), test_platform.to_string());
+        assert_eq!(
+            TiltingPlatform::parse_with(vec![String::from("@.*")], &map),
+            Err(ParseError::InvalidChar { row: 0, col: 1, found: '.' })
+        )
+    }
+
+    #[test]
+    fn test_tilt_wrapping_rows() {
+        let test_platform = TiltingPlatform::from_lines(
+            &[
+                ".O#..",
+                "..#O.",
+                "O.#.O",
+                ".O.O.",
+            ]
+        ).unwrap();
+        assert_eq!(
+            test_platform.tilt_wrapping(&Direction::West),
+            TiltingPlatform::from_lines(
+                &[
+                    "..#O.",
+                    "..#O.",
+                    "..#OO",
+                    ".O.O.",
+                ]
+            ).unwrap()
+        );
+        assert_eq!(
+            test_platform.tilt_wrapping(&Direction::East),
+            TiltingPlatform::from_lines(
+                &[
+                    ".O#..",
+                    ".O#..",
+                    "OO#..",
+                    ".O.O.",
+                ]
+            ).unwrap()
+        )
+    }
+
+    #[test]
+    fn test_tilt_wrapping_columns() {
+        let test_platform = TiltingPlatform::from_lines(
+            &[
+                ".O#..",
+                "..#O.",
+                "O.#.O",
+                ".O.O.",
+            ]
+        ).unwrap().transpose();
+        for (direction, row_direction) in [(Direction::North, Direction::West), (Direction::South, Direction::East)] {
+            assert_eq!(
+                test_platform.tilt_wrapping(&direction),
+                test_platform.transpose().tilt_wrapping(&row_direction).transpose()
+            )
+        }
+    }
+
+    #[test]
+    fn test_tilt_with_moves() {
+        let test_platform = TiltingPlatform::from_lines(
+            &[
+                "O.#",
+                ".OO",
+                "#..",
+            ]
+        ).unwrap();
+        let (stepped_platform, moves) = test_platform.tilt_with_moves(&Direction::South);
+        assert_eq!(stepped_platform, test_platform.tilt(&Direction::South));
+        assert_eq!(
+            moves,
+            vec![
+                Move { from: (0, 0), to: (1, 0) },
+                Move { from: (1, 1), to: (2, 1) },
+                Move { from: (1, 2), to: (2, 2) },
+            ]
+        )
+    }
+
+    #[test]
+    fn test_tilt_with_moves_matches_tilt() {
+        for seed in 1..=20 {
+            let test_platform = random_platform(1 + seed as usize % 7, 1 + seed as usize * 3 % 8, seed);
+            for direction in SPIN_CYCLE {
+                let (stepped_platform, moves) = test_platform.tilt_with_moves(&direction);
+                assert_eq!(stepped_platform, test_platform.tilt(&direction));
+                assert_eq!(moves.len(), test_platform.count_round());
+                for m in moves {
+                    assert_eq!(test_platform.get(m.from.0, m.from.1), Some(TPSpace::RoundStone));
+                    assert_eq!(stepped_platform.get(m.to.0, m.to.1), Some(TPSpace::RoundStone));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_ppm() {
+        let image = get_tp1().render_ppm(3);
+        let header = b"P6\n24 15\n255\n";
+        assert_eq!(&image[..header.len()], header);
+        assert_eq!(image.len(), header.len() + 24 * 15 * 3);
+        assert_eq!(&image[header.len()..header.len() + 3], &[0xdd, 0xdd, 0xdd]);
+        let square = header.len() + (2 * 3 + 14 * 24) * 3;
+        assert_eq!(&image[square..square + 3], &[0x40, 0x40, 0x40])
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let mut platforms = fixtures();
+        platforms.extend([get_tp6(), get_tp1().transpose()]);
+        let fingerprints: HashSet<u64> = platforms.iter().map(TiltingPlatform::fingerprint).collect();
+        assert_eq!(fingerprints.len(), platforms.len());
+        assert_eq!(get_tp1().fingerprint(), get_tp1().fingerprint());
+        assert_eq!(TiltingPlatform::parse(vec![]).unwrap().fingerprint(), 0xa8c7f832281a39c5)
+    }
+
+    #[test]
+    fn test_pack() {
+        for test_platform in fixtures().into_iter().chain([random_platform(13, 17, 3)]) {
+            let packed = test_platform.pack();
+            assert_eq!(packed.unpack(), test_platform);
+            assert_eq!(packed.dimensions(), test_platform.dimensions());
+            assert_eq!(packed.get_load(), test_platform.get_load());
+            for direction in SPIN_CYCLE {
+                assert_eq!(packed.tilt(&direction).unpack(), test_platform.tilt(&direction))
+            }
+        }
+        let packed = PackedPlatform::parse(vec![String::from("O.#"), String::from(".O.")]).unwrap();
+        assert_eq!(packed.get(0, 0), Some(TPSpace::RoundStone));
+        assert_eq!(packed.get(0, 2), Some(TPSpace::SquareStone));
+        assert_eq!(packed.get(1, 0), Some(TPSpace::Empty));
+        assert_eq!(packed.get(2, 0), None)
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_bytes_round_trip() {
+        for mut test_platform in fixtures().into_iter().chain([random_platform(13, 17, 3)]) {
+            test_platform.get_load_cached();
+            let bytes = test_platform.to_bytes();
+            assert_eq!(TiltingPlatform::from_bytes(&bytes).unwrap(), test_platform)
+        }
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_from_bytes_errors() {
+        let mut bytes = get_tp2().to_bytes();
+        assert!(TiltingPlatform::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        bytes.push(0);
+        assert!(TiltingPlatform::from_bytes(&bytes).is_err());
+        // One row holding a single cell whose code is not a cell kind.
+        assert!(TiltingPlatform::from_bytes(&[1, 1, 3]).is_err());
+        // Two rows, the first two cells wide and the second one.
+        let ragged = TiltingPlatform::from_bytes(&[2, 2, 0, 0, 1, 1]).unwrap_err();
+        assert!(ragged.to_string().contains(&ParseError::RaggedGrid { row: 1, expected: 2, found: 1 }.to_string()))
+    }
+
+    #[test]
+    fn test_compact_round_trip() {
+        assert_eq!(get_tp1().to_compact(), "5x8:..#..O../..#O..../O.#...../..#.O..O/..#.O.OO");
+        for test_platform in fixtures().into_iter().chain([random_platform(13, 17, 3)]) {
+            assert_eq!(TiltingPlatform::from_compact(&test_platform.to_compact()), Ok(test_platform))
+        }
+        assert_eq!(TiltingPlatform::from_compact("0x0:"), Ok(TiltingPlatform::parse(vec![]).unwrap()));
+        for rows in 1..=3 {
+            let test_platform = TiltingPlatform::from(vec![vec![]; rows]);
+            assert_eq!(TiltingPlatform::from_compact(&test_platform.to_compact()), Ok(test_platform))
+        }
+        assert_eq!(TiltingPlatform::from(vec![vec![]; 2]).to_compact(), "2x0:/")
+    }
+
+    #[test]
+    fn test_from_compact_errors() {
+        assert_eq!(TiltingPlatform::from_compact("..#/O.."), Err(CompactError::InvalidHeader));
+        assert_eq!(TiltingPlatform::from_compact("2by3:..#/O.."), Err(CompactError::InvalidHeader));
+        assert_eq!(
+            TiltingPlatform::from_compact("3x3:..#/O.."),
+            Err(CompactError::Dimensions(DimensionError { expected: (3, 3), found: (2, 3) }))
+        );
+        assert_eq!(
+            TiltingPlatform::from_compact("2x3:..#/O."),
+            Err(CompactError::Parse(ParseError::RaggedGrid { row: 1, expected: 3, found: 2 }))
+        );
+        assert_eq!(
+            TiltingPlatform::from_compact("3x0:/"),
+            Err(CompactError::Dimensions(DimensionError { expected: (3, 0), found: (2, 0) }))
+        )
+    }
+
+    #[test]
+    fn test_from_lines() {
+        assert_eq!(TiltingPlatform::from_lines(&["..O..#..", "....O#..", ".....#.O", "O..O.#.."]), Ok(get_tp2()));
+        assert_eq!(
+            TiltingPlatform::from_lines(&["..#", "O."]),
+            Err(ParseError::RaggedGrid { row: 1, expected: 3, found: 2 })
+        )
+    }
+
+    #[test]
+    fn test_parse_with_limits() {
+        let lines = || get_tp1().to_string().lines().map(String::from).collect::<Vec<_>>();
+        assert_eq!(TiltingPlatform::parse_with_limits(lines(), 5, 8), Ok(get_tp1()));
+        assert_eq!(
+            TiltingPlatform::parse_with_limits(lines(), 4, 8),
+            Err(ParseError::TooLarge { max: (4, 8), found: (5, 8) })
+        );
+        assert_eq!(
+            TiltingPlatform::parse_with_limits(lines(), 5, 7),
+            Err(ParseError::TooLarge { max: (5, 7), found: (5, 8) })
+        )
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let test_platform = TiltingPlatform::parse(
+            vec![
+                String::from("// Second fixture"),
+                String::from("..O..#.."),
+                String::from("....O#.."),
+                String::from("; halfway there"),
+                String::from(".....#.O"),
+                String::from("O..O.#.."),
+            ]
+        ).unwrap();
+        assert_eq!(test_platform, get_tp2())
+    }
+
+    #[test]
+    fn test_parse_multi() {
+        let lines: Vec<String> = [
+            "// First fixture", "..#..O..", "..#O....", "O.#.....", "..#.O..O", "..#.O.OO", "", "",
+            "; Second fixture", "..O..#..", "....O#..", ".....#.O", "O..O.#..", "  ", "// Nothing left",
+        ]
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(TiltingPlatform::parse_multi(lines).unwrap(), vec![get_tp1(), get_tp2()]);
+        assert_eq!(
+            TiltingPlatform::parse_multi(
+                vec![String::from("..#"), String::new(), String::from("..#"), String::from("O.")]
+            ),
+            Err(ParseError::RaggedGrid { row: 1, expected: 3, found: 2 })
+        )
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let (test_platform, skipped) = TiltingPlatform::parse_lenient(
+            vec![
+                String::from("..O..#.."),
+                String::from("..x.O#.."),
+                String::from(".....#.O"),
+                String::from("O..O.#?"),
+            ]
+        );
+        assert_eq!(skipped, vec![(1, 2, 'x'), (3, 6, '?')]);
+        assert_eq!(test_platform, get_tp2())
+    }
+
+    #[test]
+    fn test_tilt_step() {
+        let (stepped_platform, moved) = TiltingPlatform::from_lines(&[".OO#.O"])
+            .unwrap()
+            .tilt_step(&Direction::West);
+        assert!(moved);
+        assert_eq!(stepped_platform, TiltingPlatform::from_lines(&["O.O#O."]).unwrap())
+    }
+
+    #[test]
+    fn test_tilt_step_until_settled_matches_tilt() {
+        let mut platforms = fixtures();
+        platforms.extend((1..=10).map(|seed| random_platform(2 + seed as usize % 6, 2 + seed as usize * 5 % 7, seed)));
+        for test_platform in platforms {
+            for direction in SPIN_CYCLE {
+                let mut stepped_platform = test_platform.clone();
+                loop {
+                    let (next, moved) = stepped_platform.tilt_step(&direction);
+                    if !moved {
+                        assert_eq!(next, stepped_platform);
+                        break;
+                    }
+                    stepped_platform = next;
+                }
+                assert_eq!(stepped_platform, test_platform.tilt(&direction))
+            }
+        }
+    }
+
+    #[test]
+    fn test_tilt_matches_reference_random() {
+        for seed in 1..=40 {
+            let (rows, cols) = (1 + seed as usize * 7 % 23, 1 + seed as usize * 11 % 19);
+            let density = (seed % 8) as f64 / 10.0;
+            let mut test_platform = TiltingPlatform::random(rows, cols, density, 0.8 - density, seed);
+            test_platform.get_load_cached();
+            for direction in SPIN_CYCLE {
+                let mut reference = test_platform.tilt_reference(&direction);
+                assert_eq!(test_platform.tilt(&direction), reference);
+                assert_eq!(tilt_bitset(&test_platform, &direction), reference);
+                assert_eq!(reference.get_load_cached(), reference.get_load())
+            }
+        }
+    }
+
+    #[test]
+    fn test_center_of_mass() {
+        let test_platform = TiltingPlatform::from_lines(
+            &[
+                "O...O",
+                "..#..",
+                "O...O",
+            ]
+        ).unwrap();
+        assert_eq!(test_platform.center_of_mass(), Some((1.0, 2.0)));
+        assert_eq!(
+            test_platform.tilt(&Direction::North).center_of_mass(),
+            Some((0.5, 2.0))
+        );
+        assert_eq!(TiltingPlatform::from_lines(&["..#"]).unwrap().center_of_mass(), None)
+    }
+
+    #[test]
+    fn test_eq_up_to_rotation() {
+        let test_platform = get_tp5();
+        for quarter_turns in 0..4 {
+            assert!(test_platform.eq_up_to_rotation(&test_platform.rotate(quarter_turns)));
+            assert!(test_platform.rotate(quarter_turns).eq_up_to_rotation(&test_platform))
+        }
+        assert!(!test_platform.eq_up_to_rotation(&test_platform.transpose()));
+        assert!(!test_platform.eq_up_to_rotation(&get_tp4()))
+    }
+
+    #[test]
+    fn test_random() {
+        let test_platform = TiltingPlatform::random(40, 50, 0.25, 0.5, 7);
+        assert_eq!(test_platform, TiltingPlatform::random(40, 50, 0.25, 0.5, 7));
+        assert_ne!(test_platform, TiltingPlatform::random(40, 50, 0.25, 0.5, 8));
+        assert_eq!(test_platform.dimensions(), (40, 50));
+        assert!((400..600).contains(&test_platform.count_round()));
+        assert!((900..1100).contains(&test_platform.count_square()));
+        assert_eq!(TiltingPlatform::random(3, 4, 1.0, 0.0, 0).count_round(), 12)
+    }
+
+    #[test]
+    #[should_panic(expected = "Densities must be non negative and add up to at most 1!")]
+    fn test_random_invalid_densities() {
+        TiltingPlatform::random(3, 3, 0.6, 0.5, 1);
+    }
+
+    #[test]
+    fn test_diff() {
+        let test_platform = get_tp1();
+        assert_eq!(
+            test_platform.diff(&test_platform.tilt(&Direction::West)),
+            Ok(String::from("..#X.X..\n..#O....\nO.#.....\n..#XO..X\n..#XOXXX"))
+        );
+        assert_eq!(test_platform.diff(&test_platform), Ok(test_platform.to_string()));
+        assert_eq!(
+            test_platform.diff(&get_tp2()),
+            Err(DimensionError { expected: (5, 8), found: (4, 8) })
+        )
+    }
+
+    #[test]
+    fn test_distance() {
+        let test_platform = get_tp1();
+        let mut moved = test_platform.clone();
+        moved.set(0, 5, TPSpace::Empty);
+        moved.set(0, 6, TPSpace::RoundStone);
+        assert_eq!(test_platform.distance(&moved), Ok(2));
+        assert_eq!(test_platform.distance(&test_platform), Ok(0));
+        assert_eq!(test_platform.distance(&test_platform.tilt(&Direction::West)), Ok(8));
+        assert_eq!(
+            test_platform.distance(&get_tp2()),
+            Err(DimensionError { expected: (5, 8), found: (4, 8) })
+        )
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let test_platform = TiltingPlatform::from_reader(Cursor::new("..O..#..\n....O#..\n.....#.O\nO..O.#..\n"));
+        assert_eq!(test_platform.unwrap(), get_tp2());
+        assert!(matches!(
+            TiltingPlatform::from_reader(Cursor::new("..O\n.x.")),
+            Err(LoadError::Parse(ParseError::InvalidChar { row: 1, col: 1, found: 'x' }))
+        ))
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        assert!(matches!(TiltingPlatform::load("does-not-exist.txt"), Err(LoadError::Io(_))))
+    }
+
+    #[test]
+    fn test_cycle_many() {
+        let platforms: Vec<TiltingPlatform> = (1..=6).map(|seed| random_platform(6, 8, seed)).collect();
+        assert_eq!(
+            TiltingPlatform::cycle_many(&platforms, 1_000_000_000),
+            platforms.iter().map(|platform| platform.cycle(1_000_000_000)).collect::<Vec<TiltingPlatform>>()
+        );
+        assert_eq!(TiltingPlatform::cycle_many(&[], 10), vec![])
+    }
+
+}
@@ -0,0 +1,23 @@
+use wasm_bindgen::prelude::*;
+
+use crate::TiltingPlatform;
+
+
+/// Parses the platform in `input`, spins it `cycles` times and returns the resulting load. Errors are thrown on
+/// the JavaScript side, with the parse error as their message.
+///
+/// # Arguments
+///
+/// - `input`: the platform, one row per line.
+/// - `cycles`: number of spin cycles to run.
+#[wasm_bindgen]
+pub fn solve(input: &str, cycles: u32) -> Result<u32, JsError> {
+    try_solve(input, cycles).map_err(|e| JsError::new(&e))
+}
+
+/// Same as `solve`, but with the error message as a plain `String`, which unlike `JsError` can also be built off
+/// wasm targets.
+pub(crate) fn try_solve(input: &str, cycles: u32) -> Result<u32, String> {
+    let platform = input.parse::<TiltingPlatform>().map_err(|e| e.to_string())?;
+    u32::try_from(platform.cycle(cycles as usize).get_load()).map_err(|_| String::from("Load does not fit in a u32!"))
+}